@@ -0,0 +1,89 @@
+//! How long converting large layouts takes,
+//! once the state of the WM is known.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use layaway::{
+    absolute::{Layout, Mode, Output, OutputConfig},
+    comms::Port,
+    convert::{Context, Options},
+    geometry::{Interval, Rect, Size},
+    info::Connector,
+    relative, Map,
+};
+
+/// `count` connected screens on DisplayPort next to each other,
+/// all active at 1080p.
+fn context(count: u32) -> Context {
+    let size = Size {
+        width: 1920,
+        height: 1080,
+    };
+    let ports = (1..=count).map(|idx| Port {
+        kind: Connector::DisplayPort,
+        idx,
+    });
+
+    let current: Layout = ports
+        .clone()
+        .zip(0..)
+        .map(|(port, i)| Output {
+            port,
+            cfg: OutputConfig {
+                bounds: Rect {
+                    x: Interval::new(i * size.width, (i + 1) * size.width),
+                    y: Interval::new(0, size.height),
+                },
+                resolution: Some(size),
+                refresh: Some(60.0),
+                scale: Some(1.0),
+                active: true,
+                ..OutputConfig::default()
+            },
+        })
+        .collect();
+    let modes: Map<_, _> = ports
+        .map(|port| {
+            (
+                port,
+                vec![Mode {
+                    size,
+                    refresh: 60.0,
+                }],
+            )
+        })
+        .collect();
+
+    Context::new(current, modes)
+}
+
+fn convert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("convert");
+
+    for count in [8, 12, 16] {
+        let ctx = context(count);
+        let screens: Vec<_> = (1..=count).map(|idx| format!("dp{idx}")).collect();
+
+        let descs = [
+            ("row", screens.join(" + ")),
+            ("vertical", format!("vertical: {}", screens.join(" + "))),
+            (
+                "anchored",
+                screens.windows(2).fold(screens[0].clone(), |desc, pair| {
+                    format!("{desc} + {}/below {},left", pair[1], pair[0])
+                }),
+            ),
+        ];
+
+        for (name, desc) in descs {
+            let relative: relative::Layout = desc.parse().unwrap();
+            group.bench_with_input(BenchmarkId::new(name, count), &relative, |b, relative| {
+                b.iter(|| ctx.convert(relative, &Options::default()).unwrap());
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, convert);
+criterion_main!(benches);
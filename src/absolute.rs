@@ -1,14 +1,29 @@
+use std::{fmt, path::PathBuf};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
 use crate::{
     comms::Port,
-    geometry::{Point, Rect, Size, Transform},
+    convert,
+    geometry::{
+        Hori, HoriSpec, Interval, MaybeCenter, PhysicalSize, Pixel, Point, Rect, Size, Transform,
+        Vert, VertSpec,
+    },
     Map,
 };
 
 /// How each output should be configured,
 /// as seen from the WM.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
 pub struct Layout {
     pub outputs: Map<Port, OutputConfig>,
+    /// If [`Self::outputs`] are all outputs the WM knows about,
+    /// so that settings for all outputs at once
+    /// don't affect any other ones.
+    #[serde(default)]
+    pub complete: bool,
 }
 
 impl Layout {
@@ -23,15 +38,542 @@ impl Layout {
             .map(|(port, cfg)| OutputRef { port, cfg })
     }
 
+    /// All outputs from left to right, then top to bottom,
+    /// by the upper left corner of their bounds.
+    /// Ties are broken by port.
+    ///
+    /// Inactive outputs don't have a meaningful position,
+    /// so they come last, ordered by port.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{Layout, Output, OutputConfig},
+    /// #     comms::Port,
+    /// #     geometry::{Interval, Rect},
+    /// #     info::Connector,
+    /// # };
+    /// let output = |kind, x, y| Output {
+    ///     port: Port { kind, idx: 1 },
+    ///     cfg: OutputConfig {
+    ///         bounds: Rect { x: Interval::new(x, x + 100), y: Interval::new(y, y + 100) },
+    ///         active: true,
+    ///         ..OutputConfig::default()
+    ///     },
+    /// };
+    /// let layout: Layout = [
+    ///     output(Connector::Edp, 100, 0),
+    ///     output(Connector::DisplayPort, 0, 100),
+    ///     output(Connector::HdmiA, 0, 0),
+    /// ]
+    /// .into_iter()
+    /// .collect();
+    ///
+    /// let kinds: Vec<_> = layout
+    ///     .outputs_sorted_by_position()
+    ///     .iter()
+    ///     .map(|output| output.port.kind)
+    ///     .collect();
+    /// assert_eq!(kinds, [Connector::HdmiA, Connector::DisplayPort, Connector::Edp]);
+    /// ```
+    #[must_use]
+    pub fn outputs_sorted_by_position(&self) -> Vec<OutputRef<'_>> {
+        let mut sorted: Vec<_> = self.outputs().collect();
+        sorted.sort_by_key(|output| {
+            let bounds = output.cfg.bounds;
+            (
+                !output.cfg.active,
+                output
+                    .cfg
+                    .active
+                    .then(|| (bounds.x.start(), bounds.y.start())),
+                *output.port,
+            )
+        });
+        sorted
+    }
+
+    /// A layout which only turns `port` off if it's on in `self`, or on if it's off.
+    /// Nothing else about it or any other output is changed when applied.
+    /// [`None`] if `port` isn't in `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{Layout, Output, OutputConfig},
+    /// #     comms::{CustomNames, Port},
+    /// #     info::Connector,
+    /// # };
+    /// let port = Port { kind: Connector::DisplayPort, idx: 2 };
+    /// let output = |active| Output {
+    ///     port,
+    ///     cfg: OutputConfig { active, ..OutputConfig::default() },
+    /// };
+    /// let on: Layout = [output(true)].into_iter().collect();
+    /// let off: Layout = [output(false)].into_iter().collect();
+    ///
+    /// let names = CustomNames::default();
+    /// let commands = |layout: Layout| layout.to_sway_commands(&names).collect::<Vec<_>>();
+    /// assert_eq!(commands(on.toggled(port).unwrap()), ["output DP-2 disable"]);
+    /// assert_eq!(commands(off.toggled(port).unwrap()), ["output DP-2 enable"]);
+    /// assert!(Layout::new().toggled(port).is_none());
+    /// ```
+    #[must_use]
+    pub fn toggled(&self, port: Port) -> Option<Self> {
+        let current = self.outputs.get(&port)?;
+        let cfg = OutputConfig {
+            active: !current.active,
+            explicit: Explicit {
+                active: true,
+                ..Explicit::default()
+            },
+            ..OutputConfig::default()
+        };
+
+        Some([Output { port, cfg }].into_iter().collect())
+    }
+
+    /// A layout which exchanges where the outputs at `a` and `b` are,
+    /// by moving each to the upper left corner of the other one.
+    /// If `transforms`, their transforms are exchanged as well.
+    /// Only these two outputs are in it, with nothing else about them set.
+    /// [`None`] if either of them isn't in the layout or isn't active.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{Layout, Output, OutputConfig},
+    /// #     comms::{CustomNames, Port},
+    /// #     geometry::{Interval, Rect, Transform},
+    /// #     info::Connector,
+    /// # };
+    /// let port = |idx| Port { kind: Connector::DisplayPort, idx };
+    /// let output = |idx, x, width, transform: &str| Output {
+    ///     port: port(idx),
+    ///     cfg: OutputConfig {
+    ///         bounds: Rect { x: Interval::new(x, x + width), y: Interval::new(0, 1080) },
+    ///         transform: Transform::parse_from_sway(transform).unwrap(),
+    ///         active: true,
+    ///         ..OutputConfig::default()
+    ///     },
+    /// };
+    /// let current: Layout = [output(1, 0, 1920, "normal"), output(2, 1920, 1080, "90")]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// let names = CustomNames::default();
+    /// let commands = |layout: Layout| layout.to_sway_commands(&names).collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     commands(current.swapped(port(1), port(2), false).unwrap()),
+    ///     ["output DP-1 position 1920 0", "output DP-2 position 0 0"],
+    /// );
+    /// assert_eq!(
+    ///     commands(current.swapped(port(1), port(2), true).unwrap()),
+    ///     [
+    ///         "output DP-1 position 1920 0 transform 90",
+    ///         "output DP-2 position 0 0 transform normal",
+    ///     ],
+    /// );
+    /// assert!(current.swapped(port(1), port(3), false).is_none());
+    /// ```
+    #[must_use]
+    pub fn swapped(&self, a: Port, b: Port, transforms: bool) -> Option<Self> {
+        let active = |port| self.outputs.get(&port).filter(|cfg| cfg.active);
+        let (cfg_a, cfg_b) = (active(a)?, active(b)?);
+
+        let moved = |from: &OutputConfig, to: &OutputConfig| {
+            let (transform, size) = if transforms {
+                let turn = to.transform.rotation + -from.transform.rotation;
+                (to.transform, from.bounds.size().rotate(turn))
+            } else {
+                (from.transform, from.bounds.size())
+            };
+            let (x, y) = (to.bounds.x.start(), to.bounds.y.start());
+
+            OutputConfig {
+                bounds: Rect {
+                    x: Interval::new(x, x + size.width),
+                    y: Interval::new(y, y + size.height),
+                },
+                transform,
+                active: true,
+                explicit: Explicit {
+                    position: true,
+                    transform: transforms,
+                    ..Explicit::default()
+                },
+                ..OutputConfig::default()
+            }
+        };
+
+        Some(
+            [
+                Output {
+                    port: a,
+                    cfg: moved(cfg_a, cfg_b),
+                },
+                Output {
+                    port: b,
+                    cfg: moved(cfg_b, cfg_a),
+                },
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    /// If applying `self` wouldn't change anything about the `current` layout of the WM,
+    /// considering only what would actually be sent to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{Explicit, Layout, Output, OutputConfig, Subpixel},
+    /// #     comms::Port,
+    /// #     geometry::{Interval, Rect, Size},
+    /// #     info::Connector,
+    /// # };
+    /// let output = |x, scale| Output {
+    ///     port: Port { kind: Connector::DisplayPort, idx: 1 },
+    ///     cfg: OutputConfig {
+    ///         bounds: Rect { x: Interval::new(x, x + 1920), y: Interval::new(0, 1080) },
+    ///         scale: Some(scale),
+    ///         active: true,
+    ///         explicit: Explicit { position: true, scale: true, ..Explicit::default() },
+    ///         ..OutputConfig::default()
+    ///     },
+    /// };
+    /// let current: Layout = [output(0, 1.0)].into_iter().collect();
+    ///
+    /// assert!(current.is_in_effect(&current));
+    /// assert!(![output(1920, 1.0)].into_iter().collect::<Layout>().is_in_effect(&current));
+    /// assert!(![output(0, 2.0)].into_iter().collect::<Layout>().is_in_effect(&current));
+    /// assert!(!current.is_in_effect(&Layout::new()));
+    ///
+    /// let mode = |refresh| {
+    ///     let mut output = output(0, 1.0);
+    ///     output.cfg.resolution = Some(Size { width: 1920, height: 1080 });
+    ///     output.cfg.refresh = Some(refresh);
+    ///     output.cfg.explicit.resolution = true;
+    ///     [output].into_iter().collect::<Layout>()
+    /// };
+    /// assert!(mode(60.0).is_in_effect(&mode(59.951)));
+    /// assert!(!mode(60.0).is_in_effect(&mode(144.0)));
+    ///
+    /// let tuned = |subpixel, max_render_time| {
+    ///     let mut output = output(0, 1.0);
+    ///     output.cfg.subpixel = Some(subpixel);
+    ///     output.cfg.max_render_time = max_render_time;
+    ///     [output].into_iter().collect::<Layout>()
+    /// };
+    /// let mut current = current;
+    /// current.outputs.values_mut().for_each(|cfg| cfg.subpixel = Some(Subpixel::Rgb));
+    /// assert!(tuned(Subpixel::Rgb, None).is_in_effect(&current));
+    /// assert!(!tuned(Subpixel::Bgr, None).is_in_effect(&current));
+    /// // sway doesn't report the max render time, so it can't be known to be in effect
+    /// assert!(!tuned(Subpixel::Rgb, Some(4)).is_in_effect(&current));
+    ///
+    /// // so re-applying what was read doesn't change anything
+    /// let mut everything = current.clone();
+    /// everything.outputs.values_mut().for_each(|cfg| cfg.explicit = Explicit::ALL);
+    /// assert!(everything.is_in_effect(&current));
+    /// ```
+    #[must_use]
+    pub fn is_in_effect(&self, current: &Self) -> bool {
+        self.outputs.iter().all(|(port, cfg)| {
+            current
+                .outputs
+                .get(port)
+                .is_some_and(|cur| cfg.is_in_effect(cur))
+        })
+    }
+
+    /// If `self` and `other` are about the very same outputs
+    /// or about completely different ones,
+    /// so switching between them doesn't leave
+    /// some outputs half-configured by the one before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{Layout, Output, OutputConfig},
+    /// #     comms::Port,
+    /// #     info::Connector,
+    /// # };
+    /// let layout = |ports: &[Connector]| -> Layout {
+    ///     ports
+    ///         .iter()
+    ///         .map(|&kind| Output { port: Port { kind, idx: 1 }, cfg: OutputConfig::default() })
+    ///         .collect()
+    /// };
+    /// let docked = layout(&[Connector::Edp, Connector::DisplayPort]);
+    ///
+    /// assert!(docked.compatible_with(&docked));
+    /// assert!(docked.compatible_with(&layout(&[Connector::HdmiA])));
+    /// assert!(!docked.compatible_with(&layout(&[Connector::Edp])));
+    /// ```
+    #[must_use]
+    pub fn compatible_with(&self, other: &Self) -> bool {
+        let same = self.outputs.keys().eq(other.outputs.keys());
+        let disjoint = !self
+            .outputs
+            .keys()
+            .any(|port| other.outputs.contains_key(port));
+        same || disjoint
+    }
+
+    /// Which outputs switching from `self` to `other` would add, remove or change.
+    /// An output is changed if applying its configuration in `other`
+    /// on top of the one in `self` would change anything,
+    /// like for [`Self::is_in_effect`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{Explicit, Layout, Output, OutputConfig, Relationship},
+    /// #     comms::Port,
+    /// #     geometry::{Interval, Rect},
+    /// #     info::Connector,
+    /// # };
+    /// let output = |kind, x| Output {
+    ///     port: Port { kind, idx: 1 },
+    ///     cfg: OutputConfig {
+    ///         bounds: Rect { x: Interval::new(x, x + 1920), y: Interval::new(0, 1080) },
+    ///         active: true,
+    ///         explicit: Explicit { position: true, ..Explicit::default() },
+    ///         ..OutputConfig::default()
+    ///     },
+    /// };
+    /// let before: Layout = [output(Connector::Edp, 0), output(Connector::DisplayPort, 1920)]
+    ///     .into_iter()
+    ///     .collect();
+    /// let after: Layout = [output(Connector::Edp, 1920), output(Connector::HdmiA, 0)]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// let port = |kind| Port { kind, idx: 1 };
+    /// assert_eq!(
+    ///     before.relationship(&after),
+    ///     Relationship {
+    ///         added: vec![port(Connector::HdmiA)],
+    ///         removed: vec![port(Connector::DisplayPort)],
+    ///         changed: vec![port(Connector::Edp)],
+    ///     },
+    /// );
+    /// assert!(before.relationship(&before).is_empty());
+    /// ```
+    #[must_use]
+    pub fn relationship(&self, other: &Self) -> Relationship {
+        let mut rel = Relationship::default();
+        for (port, cfg) in &other.outputs {
+            match self.outputs.get(port) {
+                None => rel.added.push(*port),
+                Some(cur) if !cfg.is_in_effect(cur) => rel.changed.push(*port),
+                Some(_) => {}
+            }
+        }
+        rel.removed = self
+            .outputs
+            .keys()
+            .filter(|port| !other.outputs.contains_key(port))
+            .copied()
+            .collect();
+        rel
+    }
+
     pub fn add(&mut self, output: Output) {
         self.outputs.insert(output.port, output.cfg);
     }
 
+    /// All pairs of outputs which share a part of an edge,
+    /// so that the cursor and windows can move between them.
+    /// Each pair is only listed once.
+    pub fn neighbors(&self) -> impl Iterator<Item = (OutputRef<'_>, OutputRef<'_>)> {
+        self.outputs().enumerate().flat_map(move |(i, a)| {
+            self.outputs()
+                .skip(i + 1)
+                .filter(move |b| a.cfg.bounds.is_adjacent(&b.cfg.bounds))
+                .map(move |b| (a, b))
+        })
+    }
+
+    /// All pairs of outputs which share some area,
+    /// meaning one would hide parts of the other one.
+    /// Each pair is only listed once.
+    pub fn overlapping(&self) -> impl Iterator<Item = (OutputRef<'_>, OutputRef<'_>)> {
+        self.outputs().enumerate().flat_map(move |(i, a)| {
+            self.outputs()
+                .skip(i + 1)
+                .filter(move |b| a.cfg.bounds.intersects(&b.cfg.bounds))
+                .map(move |b| (a, b))
+        })
+    }
+
+    /// All pairs of outputs which only touch at a corner,
+    /// so that the cursor can't move between them there.
+    /// Each pair is only listed once.
+    pub fn touching_only_at_corner(&self) -> impl Iterator<Item = (OutputRef<'_>, OutputRef<'_>)> {
+        self.outputs().enumerate().flat_map(move |(i, a)| {
+            self.outputs()
+                .skip(i + 1)
+                .filter(move |b| a.cfg.bounds.touches_only_at_corner(&b.cfg.bounds))
+                .map(move |b| (a, b))
+        })
+    }
+
+    /// Runs all checks about the layout being usable at once,
+    /// namely if outputs overlap, only touch at a corner,
+    /// can't be reached from the others at all,
+    /// would have a fractional logical size
+    /// or reach further than `max` from the origin,
+    /// usually [`convert::Options::max_coordinate`].
+    /// Inactive outputs are not checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{AuditFinding, AuditKind, Layout, Output, OutputConfig, Severity},
+    /// #     comms::Port,
+    /// #     convert::Options,
+    /// #     geometry::{Interval, Rect, Size},
+    /// #     info::Connector,
+    /// # };
+    /// let output = |idx, x, y| Output {
+    ///     port: Port { kind: Connector::DisplayPort, idx },
+    ///     cfg: OutputConfig {
+    ///         bounds: Rect { x: Interval::new(x, x + 100), y: Interval::new(y, y + 100) },
+    ///         resolution: Some(Size { width: 100, height: 100 }),
+    ///         scale: Some(1.0),
+    ///         active: true,
+    ///         ..Default::default()
+    ///     },
+    /// };
+    ///
+    /// let max = Options::DEFAULT_MAX_COORDINATE;
+    /// let layout: Layout = [output(1, 0, 0), output(2, 100, 50)].into_iter().collect();
+    /// assert!(layout.audit(max).is_empty());
+    /// // the second one reaches until 200,150
+    /// let findings = layout.audit(120);
+    /// assert!(matches!(findings[..], [AuditFinding { kind: AuditKind::OutOfBounds { .. }, .. }]));
+    ///
+    /// let layout: Layout = [output(1, 0, 0), output(2, 100, 100)].into_iter().collect();
+    /// let findings = layout.audit(max);
+    /// assert_eq!(findings.len(), 2);
+    /// assert!(findings.iter().all(|finding| finding.severity == Severity::Warning));
+    /// assert_eq!(findings[0].kind, AuditKind::CornerOnly);
+    /// assert_eq!(findings[1].kind, AuditKind::Island);
+    /// assert_eq!(findings[1].ports, [Port { kind: Connector::DisplayPort, idx: 2 }]);
+    /// ```
+    #[must_use]
+    pub fn audit(&self, max: Pixel) -> Vec<AuditFinding> {
+        let active = Self {
+            outputs: self
+                .outputs
+                .iter()
+                .filter(|(_, cfg)| cfg.active)
+                .map(|(port, cfg)| (*port, cfg.clone()))
+                .collect(),
+            complete: self.complete,
+        };
+        let pair = |kind: AuditKind, (a, b): (OutputRef<'_>, OutputRef<'_>)| AuditFinding {
+            severity: Severity::Warning,
+            ports: vec![*a.port, *b.port],
+            kind,
+        };
+
+        let mut findings: Vec<_> = active
+            .overlapping()
+            .map(|outputs| pair(AuditKind::Overlap, outputs))
+            .collect();
+        findings.extend(
+            active
+                .touching_only_at_corner()
+                .map(|outputs| pair(AuditKind::CornerOnly, outputs)),
+        );
+        findings.extend(
+            active
+                .islands()
+                .into_iter()
+                .skip(1)
+                .map(|ports| AuditFinding {
+                    severity: Severity::Warning,
+                    ports,
+                    kind: AuditKind::Island,
+                }),
+        );
+
+        for output in active.outputs() {
+            let port = *output.port;
+            if let (Some(resolution), Some(scale)) = (output.cfg.resolution, output.cfg.scale) {
+                if let Some(convert::Warning::FractionalLogicalSize { suggestion, .. }) =
+                    convert::fractional_logical_size(port, resolution, scale)
+                {
+                    findings.push(AuditFinding {
+                        severity: Severity::Warning,
+                        ports: vec![port],
+                        kind: AuditKind::FractionalLogicalSize {
+                            resolution,
+                            scale,
+                            suggestion,
+                        },
+                    });
+                }
+            }
+
+            let bounds = output.cfg.bounds;
+            if convert::check_bounds(port, bounds, max).is_err() {
+                findings.push(AuditFinding {
+                    severity: Severity::Error,
+                    ports: vec![port],
+                    kind: AuditKind::OutOfBounds { bounds, max },
+                });
+            }
+        }
+
+        findings
+    }
+
+    /// Groups the outputs into sets which are connected through [`Self::neighbors`],
+    /// so the cursor can move anywhere within one set, but not between them.
+    /// Each set is ordered by port,
+    /// the sets are ordered by their first port.
+    fn islands(&self) -> Vec<Vec<Port>> {
+        let mut islands: Vec<Vec<Port>> = self.outputs.keys().map(|port| vec![*port]).collect();
+        for (a, b) in self.neighbors() {
+            let find = |port: &Port| {
+                islands
+                    .iter()
+                    .position(|island| island.contains(port))
+                    .expect("every port is in exactly one island")
+            };
+            let (i, j) = (find(a.port), find(b.port));
+            if i == j {
+                continue;
+            }
+            let (keep, merge) = (i.min(j), i.max(j));
+            let merged = islands.remove(merge);
+            islands[keep].extend(merged);
+            islands[keep].sort_unstable();
+        }
+        islands
+    }
+
     /// The smallest rectangle that includes all output bounds.
     pub fn bounding_box(&self) -> Rect {
-        let mut bb = Rect::default();
-        for cfg in self.outputs.values() {
-            bb.stretch_to_rect(cfg.bounds);
+        // not starting at the default, it'd always contain the origin otherwise
+        let mut bounds = self.outputs.values().map(|cfg| cfg.bounds);
+        let Some(mut bb) = bounds.next() else {
+            return Rect::default();
+        };
+        for rect in bounds {
+            bb.stretch_to_rect(rect);
         }
         bb
     }
@@ -52,12 +594,269 @@ impl Layout {
         };
 
         // then actually do move everything
+        self.translate(-least);
+    }
+
+    /// Move all outputs so that the one at `port` starts at the origin.
+    /// Their relative positions to each other aren't changed,
+    /// so others might end up in the negative space.
+    /// If `port` isn't in the layout, nothing is moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{Layout, Output, OutputConfig},
+    /// #     comms::Port,
+    /// #     geometry::{Interval, Point, Rect},
+    /// #     info::Connector,
+    /// # };
+    /// let output = |kind, x| Output {
+    ///     port: Port { kind, idx: 1 },
+    ///     cfg: OutputConfig {
+    ///         bounds: Rect { x: Interval::new(x, x + 1920), y: Interval::new(0, 1080) },
+    ///         ..OutputConfig::default()
+    ///     },
+    /// };
+    /// let mut layout: Layout = [output(Connector::Edp, 0), output(Connector::DisplayPort, 1920)]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// let dp = Port { kind: Connector::DisplayPort, idx: 1 };
+    /// layout.anchor_port_at_origin(dp);
+    /// assert_eq!(layout.outputs[&dp].bounds.x.start(), 0);
+    /// assert_eq!(layout.bounding_box().x.start(), -1920);
+    /// ```
+    pub fn anchor_port_at_origin(&mut self, port: Port) {
+        let Some(cfg) = self.outputs.get(&port) else {
+            return;
+        };
+
+        let start = Point {
+            x: cfg.bounds.x.start(),
+            y: cfg.bounds.y.start(),
+        };
+        self.translate(-start);
+    }
+
+    /// Moves outputs vertically so each row lines up
+    /// like its tallest output at the top, center or bottom,
+    /// instead of only how they were placed next to each other.
+    ///
+    /// A row is a sequence of outputs directly right of each other,
+    /// sharing a part of their vertical edges.
+    /// Outputs without neighbor to their left or right aren't moved,
+    /// and nothing is moved horizontally.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{Layout, Output, OutputConfig},
+    /// #     comms::Port,
+    /// #     geometry::{Interval, Rect, Vert, VertSpec},
+    /// #     info::Connector,
+    /// # };
+    /// let output = |idx, x, height| Output {
+    ///     port: Port { kind: Connector::DisplayPort, idx },
+    ///     cfg: OutputConfig {
+    ///         bounds: Rect { x: Interval::new(x, x + 1920), y: Interval::new(0, height) },
+    ///         ..OutputConfig::default()
+    ///     },
+    /// };
+    /// // tops aligned, as the DSL places them by default
+    /// let mut layout: Layout = [output(1, 0, 1080), output(2, 1920, 1200), output(3, 3840, 1080)]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// layout.align_row(VertSpec::Extreme(Vert::Bottom));
+    /// let bottoms: Vec<_> = layout.outputs.values().map(|cfg| cfg.bounds.y.end()).collect();
+    /// assert_eq!(bottoms, [1200, 1200, 1200]);
+    ///
+    /// layout.align_row(VertSpec::Center);
+    /// let tops: Vec<_> = layout.outputs.values().map(|cfg| cfg.bounds.y.start()).collect();
+    /// assert_eq!(tops, [60, 0, 60]);
+    /// ```
+    pub fn align_row(&mut self, spec: VertSpec) {
+        for row in self.rows() {
+            let Some(tallest) = row
+                .iter()
+                .map(|port| self.outputs[port].bounds.y)
+                .max_by_key(|y| (y.len(), -y.start()))
+            else {
+                continue;
+            };
+
+            for port in row {
+                let Some(cfg) = self.outputs.get_mut(&port) else {
+                    continue;
+                };
+                let y = &mut cfg.bounds.y;
+                let len = y.len();
+                let start = match spec {
+                    MaybeCenter::Extreme(Vert::Top) => tallest.start(),
+                    MaybeCenter::Center => tallest.mid() - len / 2,
+                    MaybeCenter::Extreme(Vert::Bottom) => tallest.end() - len,
+                    MaybeCenter::Along(_) => {
+                        tallest.place_inside(len, spec.map(Into::into)).start()
+                    }
+                };
+                *y = Interval::new(start, start + len);
+            }
+        }
+    }
+
+    /// Like [`Self::align_row`], but moves outputs horizontally
+    /// so each column lines up like its widest output
+    /// at the left, center or right.
+    ///
+    /// A column is a sequence of outputs directly below each other,
+    /// sharing a part of their horizontal edges.
+    pub fn align_column(&mut self, spec: HoriSpec) {
+        for column in self.columns() {
+            let Some(widest) = column
+                .iter()
+                .map(|port| self.outputs[port].bounds.x)
+                .max_by_key(|x| (x.len(), -x.start()))
+            else {
+                continue;
+            };
+
+            for port in column {
+                let Some(cfg) = self.outputs.get_mut(&port) else {
+                    continue;
+                };
+                let x = &mut cfg.bounds.x;
+                let len = x.len();
+                let start = match spec {
+                    MaybeCenter::Extreme(Hori::Left) => widest.start(),
+                    MaybeCenter::Center => widest.mid() - len / 2,
+                    MaybeCenter::Extreme(Hori::Right) => widest.end() - len,
+                    MaybeCenter::Along(_) => widest.place_inside(len, spec.map(Into::into)).start(),
+                };
+                *x = Interval::new(start, start + len);
+            }
+        }
+    }
+
+    /// All rows of at least 2 outputs, see [`Self::align_row`].
+    /// Each output is in at most one row.
+    fn rows(&self) -> Vec<Vec<Port>> {
+        self.chains(|a, b| a.x.touches(&b.x) && a.y.overlaps(&b.y))
+    }
+
+    /// All columns of at least 2 outputs, see [`Self::align_column`].
+    /// Each output is in at most one column.
+    fn columns(&self) -> Vec<Vec<Port>> {
+        self.chains(|a, b| a.y.touches(&b.y) && a.x.overlaps(&b.x))
+    }
+
+    /// Groups outputs which are `next_to` each other,
+    /// leaving out the ones which aren't next to any.
+    fn chains(&self, next_to: impl Fn(&Rect, &Rect) -> bool) -> Vec<Vec<Port>> {
+        let mut chains: Vec<Vec<Port>> = Vec::new();
+        for output in self.outputs_sorted_by_position() {
+            let joining = chains.iter_mut().find(|chain| {
+                chain
+                    .iter()
+                    .any(|other| next_to(&self.outputs[other].bounds, &output.cfg.bounds))
+            });
+            match joining {
+                Some(chain) => chain.push(*output.port),
+                None => chains.push(vec![*output.port]),
+            }
+        }
+
+        chains.retain(|chain| chain.len() > 1);
+        chains
+    }
+
+    /// Moves each active output by as little as possible so it lies within `region`.
+    /// Outputs larger than `region` can't fit at all,
+    /// so they're left out and returned instead.
+    ///
+    /// Since each output is moved on its own,
+    /// outputs may end up overlapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{Layout, Output, OutputConfig},
+    /// #     comms::Port,
+    /// #     geometry::{Interval, Rect},
+    /// #     info::Connector,
+    /// # };
+    /// let rect = |x, y, width, height| Rect {
+    ///     x: Interval::new(x, x + width),
+    ///     y: Interval::new(y, y + height),
+    /// };
+    /// let port = |idx| Port { kind: Connector::DisplayPort, idx };
+    /// let output = |idx, bounds| Output {
+    ///     port: port(idx),
+    ///     cfg: OutputConfig { bounds, active: true, ..OutputConfig::default() },
+    /// };
+    /// let mut layout: Layout = [
+    ///     output(1, rect(0, 0, 1920, 1080)),
+    ///     // partially outside
+    ///     output(2, rect(1920, 0, 1920, 1080)),
+    ///     // fully outside
+    ///     output(3, rect(0, 5000, 1280, 720)),
+    ///     // can't fit at all
+    ///     output(4, rect(3840, 0, 3840, 2160)),
+    /// ]
+    /// .into_iter()
+    /// .collect();
+    ///
+    /// let left_out = layout.clamp_into(rect(0, 0, 2560, 1440));
+    /// assert_eq!(left_out, [port(4)]);
+    ///
+    /// let bounds = |idx| layout.outputs[&port(idx)].bounds;
+    /// assert_eq!(bounds(1), rect(0, 0, 1920, 1080));
+    /// assert_eq!(bounds(2), rect(640, 0, 1920, 1080));
+    /// assert_eq!(bounds(3), rect(0, 720, 1280, 720));
+    /// ```
+    #[must_use]
+    pub fn clamp_into(&mut self, region: Rect) -> Vec<Port> {
+        let too_large: Vec<_> = self
+            .outputs()
+            .filter(|output| {
+                let bounds = output.cfg.bounds;
+                output.cfg.active
+                    && (bounds.x.len() > region.x.len() || bounds.y.len() > region.y.len())
+            })
+            .map(|output| *output.port)
+            .collect();
+
+        for port in &too_large {
+            self.outputs.remove(port);
+        }
+        for cfg in self.outputs.values_mut().filter(|cfg| cfg.active) {
+            cfg.bounds = Rect {
+                x: cfg.bounds.x.clamp(region.x),
+                y: cfg.bounds.y.clamp(region.y),
+            };
+        }
+
+        too_large
+    }
+
+    /// Move all outputs by `offset`.
+    pub fn translate(&mut self, offset: Point) {
         for cfg in self.outputs.values_mut() {
-            cfg.bounds -= least;
+            cfg.bounds += offset;
         }
     }
 }
 
+/// If sending `wanted` wouldn't change `current`.
+/// [`None`] for `wanted` means it isn't sent at all,
+/// for `current` that the WM doesn't report it,
+/// in which case it's rather assumed to differ than to be the same.
+fn unchanged_by<T: PartialEq>(wanted: Option<T>, current: Option<T>) -> bool {
+    wanted.is_none() || wanted == current
+}
+
 impl FromIterator<Output> for Layout {
     fn from_iter<I: IntoIterator<Item = Output>>(iter: I) -> Self {
         let outputs = iter
@@ -65,12 +864,15 @@ impl FromIterator<Output> for Layout {
             .map(|Output { port, cfg }| (port, cfg))
             .collect();
 
-        Self { outputs }
+        Self {
+            outputs,
+            complete: false,
+        }
     }
 }
 
 /// Something that the WM can display to. Usually a screen.
-#[derive(Copy, Clone, PartialEq, PartialOrd)]
+#[derive(Clone, PartialEq, PartialOrd)]
 pub struct Output {
     /// Where this output is physically connected.
     pub port: Port,
@@ -100,8 +902,130 @@ impl<'a> From<&'a Output> for OutputRef<'a> {
     }
 }
 
+/// How the outputs of two layouts relate, see [`Layout::relationship`].
+/// All ports are ordered.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Relationship {
+    /// Only in the layout switched to.
+    pub added: Vec<Port>,
+    /// Only in the layout switched from.
+    pub removed: Vec<Port>,
+    /// In both, but configured differently in the one switched to.
+    pub changed: Vec<Port>,
+}
+
+impl Relationship {
+    /// If switching wouldn't affect any output at all.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Something questionable about a layout, found by [`Layout::audit`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditFinding {
+    /// How much this stops the layout from working.
+    pub severity: Severity,
+    /// Which outputs are involved, ordered by port.
+    pub ports: Vec<Port>,
+    pub kind: AuditKind,
+}
+
+/// How bad an [`AuditFinding`] is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The layout works, but probably not as intended.
+    Warning,
+    /// The WM will reject or mangle the layout.
+    Error,
+}
+
+/// What an [`AuditFinding`] is about.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuditKind {
+    /// Both outputs cover the same area,
+    /// so parts of one of them are hidden.
+    Overlap,
+    /// Both outputs only touch at a corner,
+    /// so the cursor can't move between them,
+    /// even though they look connected.
+    /// This easily happens when centering screens of different sizes.
+    CornerOnly,
+    /// The outputs share no edge with any of the other outputs,
+    /// so the cursor can't reach them.
+    /// The set with the first port is considered the main one
+    /// and never reported.
+    Island,
+    /// Like [`convert::Warning::FractionalLogicalSize`].
+    FractionalLogicalSize {
+        resolution: Size,
+        scale: f64,
+        suggestion: Option<f64>,
+    },
+    /// Like [`convert::Error::OutOfBounds`].
+    OutOfBounds { bounds: Rect, max: Pixel },
+}
+
+impl fmt::Display for AuditFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ports = self
+            .ports
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match &self.kind {
+            AuditKind::Overlap => write!(
+                f,
+                "{ports} overlap, \
+                so parts of one of them are hidden behind the other one."
+            ),
+            AuditKind::CornerOnly => write!(
+                f,
+                "{ports} only touch at a corner, \
+                so the cursor can't move between them. \
+                Consider moving one of them a bit, e.g. with an offset, \
+                so they share part of an edge."
+            ),
+            AuditKind::Island => write!(
+                f,
+                "{ports} share no edge with the other outputs, \
+                so the cursor can't reach them."
+            ),
+            // only ever about one output
+            &AuditKind::FractionalLogicalSize {
+                resolution,
+                scale,
+                suggestion,
+            } => write!(
+                f,
+                "{}",
+                convert::Warning::FractionalLogicalSize {
+                    port: self.ports[0],
+                    resolution,
+                    scale,
+                    suggestion,
+                },
+            ),
+            &AuditKind::OutOfBounds { bounds, max } => write!(
+                f,
+                "{}",
+                convert::Error::OutOfBounds {
+                    port: self.ports[0],
+                    bounds,
+                    max,
+                },
+            ),
+        }
+    }
+}
+
 /// Configuration for a given output in the WM.
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+///
+/// [`Layout::outputs`] hands out [`OutputRef`]s to avoid cloning.
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize, JsonSchema)]
 pub struct OutputConfig {
     /// Where this output is placed in the WM.
     pub bounds: Rect,
@@ -110,13 +1034,416 @@ pub struct OutputConfig {
     /// [`None`] if the screen is not active.
     pub resolution: Option<Size>,
 
+    /// How often the screen is redrawn per second, in Hz.
+    /// [`None`] if the screen is not active
+    /// or the WM may choose the refresh rate.
+    pub refresh: Option<f64>,
+
+    /// Between which refresh rates the screen may vary,
+    /// for variable refresh rate.
+    /// [`None`] leaves whatever the WM currently uses untouched.
+    ///
+    /// Only Sway knows about this, other WMs ignore it.
+    /// Sway can't be told the range itself,
+    /// so it only turns on adaptive sync,
+    /// with [`Self::refresh`] as the maximum.
+    /// Sway does not report it back over IPC,
+    /// so it is always [`None`] when read from the WM.
+    #[serde(default)]
+    pub refresh_range: Option<RefreshRange>,
+
     /// With what size multiplier to have applications rendered
     /// if they are visible on this output.
-    pub scale: f64,
+    /// [`None`] if the WM doesn't report one,
+    /// e.g. since the output is inactive and hence has no meaningful scale.
+    ///
+    /// # Examples
+    ///
+    /// Turning on an inactive output doesn't make up a scale for it:
+    ///
+    /// ```
+    /// # use layaway::{absolute::{Layout, Output, OutputConfig}, comms::{self, Comms, Port}};
+    /// # use layaway::{info::Connector, relative};
+    /// struct Off;
+    /// impl Comms for Off {
+    ///     fn layout(&mut self) -> comms::Result<Layout> {
+    ///         let port = Port { kind: Connector::Edp, idx: 1 };
+    ///         Ok([Output { port, cfg: OutputConfig::default() }].into_iter().collect())
+    ///     }
+    ///     # fn set_layout(&mut self, _: &Layout) -> comms::Result<()> {
+    ///     #     Ok(())
+    ///     # }
+    /// }
+    ///
+    /// let relative: relative::Layout = "edp @ 1080p".parse()?;
+    /// let layout = relative.to_absolute(&mut Off)?.layout;
+    /// let cfg = &layout.outputs[&Port { kind: Connector::Edp, idx: 1 }];
+    /// assert_eq!(cfg.scale, Some(1.0));
+    /// assert!(!cfg.explicit.scale);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub scale: Option<f64>,
 
     /// How the output is flipped and rotated.
     pub transform: Transform,
 
     /// If the output is currently on and displaying.
     pub active: bool,
+
+    /// How many milliseconds before the next frame
+    /// the WM should start rendering it.
+    /// [`None`] leaves whatever the WM currently uses untouched.
+    ///
+    /// Only Sway knows about this, other WMs ignore it.
+    /// Sway does not report it back over IPC,
+    /// so it is always [`None`] when read from the WM.
+    pub max_render_time: Option<u32>,
+
+    /// In which order and orientation the color elements of each pixel are arranged,
+    /// used to make fonts look smoother.
+    /// [`None`] leaves whatever the WM currently uses untouched.
+    ///
+    /// Only Sway knows about this, other WMs ignore it.
+    pub subpixel: Option<Subpixel>,
+
+    /// How many bits per color channel the WM should render with,
+    /// either 8 or 10.
+    /// [`None`] leaves whatever the WM currently uses untouched.
+    ///
+    /// Only Sway knows about this, other WMs ignore it.
+    /// Sway does not report it back over IPC,
+    /// so it is always [`None`] when read from the WM.
+    #[serde(default)]
+    pub render_bit_depth: Option<u8>,
+
+    /// If the screen is powered on and hence shows anything.
+    /// [`None`] leaves whatever the WM currently uses untouched.
+    ///
+    /// Unlike an inactive output, one that's powered off
+    /// is still part of the layout and keeps its workspaces,
+    /// it's just dark.
+    ///
+    /// Only Sway knows about this, other WMs ignore it.
+    #[serde(default)]
+    pub power: Option<bool>,
+
+    /// What to show behind all windows.
+    /// [`None`] leaves whatever the WM currently uses untouched.
+    ///
+    /// Only part of exported Sway configuration, it is never applied to the WM,
+    /// since the background usually doesn't change while running.
+    /// Sway does not report it back over IPC,
+    /// so it is always [`None`] when read from the WM.
+    #[serde(default)]
+    pub background: Option<Background>,
+
+    /// How large the screen is in reality, in its unrotated orientation.
+    /// [`None`] if unknown, e.g. for projectors.
+    ///
+    /// Only informational, it is never applied to the WM.
+    pub physical_size: Option<PhysicalSize>,
+
+    /// Which screen this is, regardless of where it's plugged in.
+    /// [`None`] if the WM doesn't tell.
+    ///
+    /// Only informational, it is never applied to the WM.
+    #[serde(default)]
+    pub identity: Option<Identity>,
+
+    /// If the output doesn't exist yet and the WM has to create it first.
+    /// Only possible for [headless] outputs.
+    ///
+    /// [headless]: crate::info::Connector::Headless
+    #[serde(default)]
+    pub create: bool,
+
+    /// Which of the fields above are actually sent to the WM when applied.
+    /// The others are left untouched in the WM.
+    #[serde(default)]
+    pub explicit: Explicit,
+}
+
+/// Selects fields of [`OutputConfig`].
+/// For the ones not listed here, [`None`] means that they aren't selected.
+#[allow(clippy::struct_excessive_bools)] // they're all independent
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+)]
+pub struct Explicit {
+    /// The start of [`OutputConfig::bounds`].
+    pub position: bool,
+    /// [`OutputConfig::resolution`] and [`OutputConfig::refresh`].
+    pub resolution: bool,
+    pub scale: bool,
+    pub transform: bool,
+    /// [`OutputConfig::active`] being false, so the output is disabled.
+    /// Otherwise, inactive outputs are left as they are.
+    /// Active ones are always enabled anyway.
+    #[serde(default)]
+    pub active: bool,
+}
+
+impl Explicit {
+    /// Every field is selected.
+    pub const ALL: Self = Self {
+        position: true,
+        resolution: true,
+        scale: true,
+        transform: true,
+        active: true,
+    };
+}
+
+/// Resolution and refresh rate a screen can be driven at.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Serialize)]
+pub struct Mode {
+    pub size: Size,
+    /// In Hz.
+    pub refresh: f64,
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}Hz", self.size, self.refresh)
+    }
+}
+
+/// Lowest and highest refresh rate a screen may vary between,
+/// for variable refresh rate.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize, JsonSchema)]
+pub struct RefreshRange {
+    /// In Hz.
+    pub min: f64,
+    /// In Hz, at least [`Self::min`].
+    pub max: f64,
+}
+
+impl fmt::Display for RefreshRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}Hz", self.min, self.max)
+    }
+}
+
+/// Layout of the color elements within one pixel.
+/// Formatted and parsed as how Sway calls them.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Display,
+    EnumString,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Subpixel {
+    /// Red, green, blue from left to right.
+    Rgb,
+    /// Blue, green, red from left to right.
+    Bgr,
+    /// Red, green, blue from top to bottom.
+    Vrgb,
+    /// Blue, green, red from top to bottom.
+    Vbgr,
+    /// No subpixel hinting at all.
+    None,
+}
+
+/// Make, model and serial number of a screen, as the WM reports them.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize, JsonSchema,
+)]
+pub struct Identity {
+    /// Manufacturer, either spelled out or as 3-letter PNP ID, depending on the WM.
+    pub make: String,
+    pub model: String,
+    /// [`None`] if the screen doesn't have one.
+    pub serial: Option<String>,
+}
+
+impl Identity {
+    /// If the words of `spec` appear in this order as whole words
+    /// in how this identity is formatted,
+    /// comparing them like names in layout descriptions:
+    /// Ignoring case and everything but letters and digits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::absolute::Identity;
+    /// let identity = Identity {
+    ///     make: "Dell Inc.".to_string(),
+    ///     model: "DELL U2720Q".to_string(),
+    ///     serial: Some("ABC123".to_string()),
+    /// };
+    /// assert!(identity.matches("dell u2720q"));
+    /// assert!(identity.matches("U2720Q ABC123"));
+    /// assert!(identity.matches("Dell Inc"));
+    /// assert!(!identity.matches("U2720Q XYZ"));
+    /// // only whole words, so a similar model isn't mistaken for this one
+    /// assert!(!identity.matches("U2720"));
+    /// assert!(!identity.matches(""));
+    /// ```
+    #[must_use]
+    pub fn matches(&self, spec: &str) -> bool {
+        let words = |s: &str| -> Vec<String> {
+            s.split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(str::to_lowercase)
+                .collect()
+        };
+        let spec = words(spec);
+        !spec.is_empty()
+            && words(&self.to_string())
+                .windows(spec.len())
+                .any(|window| window == spec)
+    }
+}
+
+/// Make, model and serial separated by spaces, leaving out what's unknown.
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.make, self.model)?;
+        if let Some(serial) = &self.serial {
+            write!(f, " {serial}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An image shown behind all windows on an output.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize, JsonSchema,
+)]
+pub struct Background {
+    /// Where the image is stored.
+    /// Sway expands `~` and environment variables in it.
+    pub path: PathBuf,
+    /// How the image is fit onto the output.
+    pub mode: BackgroundMode,
+}
+
+/// How a [`Background`] image is fit onto an output
+/// if their sizes differ.
+/// Formatted and parsed as how Sway calls them.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Display,
+    EnumString,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum BackgroundMode {
+    /// Scaled so that it covers the output, cutting off what's too much.
+    Fill,
+    /// Scaled so that it's entirely visible, leaving bars where it's too small.
+    Fit,
+    /// Scaled to exactly the output size, ignoring its aspect ratio.
+    Stretch,
+    /// Not scaled at all, but centered on the output.
+    Center,
+    /// Not scaled at all, but repeated to cover the output.
+    Tile,
+}
+
+impl Default for OutputConfig {
+    /// An inactive output at the origin with no size,
+    /// where nothing would be sent to the WM.
+    fn default() -> Self {
+        Self {
+            bounds: Rect::default(),
+            resolution: None,
+            refresh: None,
+            refresh_range: None,
+            scale: None,
+            transform: Transform::default(),
+            active: false,
+            max_render_time: None,
+            subpixel: None,
+            render_bit_depth: None,
+            power: None,
+            background: None,
+            physical_size: None,
+            identity: None,
+            create: false,
+            explicit: Explicit::default(),
+        }
+    }
+}
+
+impl OutputConfig {
+    /// If applying `self` wouldn't change anything about `current`,
+    /// see [`Layout::is_in_effect`].
+    fn is_in_effect(&self, current: &Self) -> bool {
+        /// How much scales may differ,
+        /// since WMs tend to report them slightly off.
+        const SCALE_TOLERANCE: f64 = 1e-3;
+
+        let (cfg, cur) = (self, current);
+        if !cfg.active {
+            return !cfg.explicit.active || !cur.active;
+        }
+
+        let same_scale = match (cfg.scale, cur.scale) {
+            (Some(a), Some(b)) => (a - b).abs() < SCALE_TOLERANCE,
+            (a, b) => a == b,
+        };
+        // the refresh rate is sent along with the resolution
+        let same_refresh = match (cfg.refresh, cur.refresh) {
+            (Some(a), Some(b)) => (a - b).abs() <= convert::REFRESH_TOLERANCE,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        cur.active
+            && cfg.bounds == cur.bounds
+            && (!cfg.explicit.resolution || (cfg.resolution == cur.resolution && same_refresh))
+            && (!cfg.explicit.scale || same_scale)
+            && (!cfg.explicit.transform || cfg.transform == cur.transform)
+            && unchanged_by(cfg.refresh_range, cur.refresh_range)
+            && unchanged_by(cfg.max_render_time, cur.max_render_time)
+            && unchanged_by(cfg.subpixel, cur.subpixel)
+            && unchanged_by(cfg.render_bit_depth, cur.render_bit_depth)
+            && unchanged_by(cfg.power, cur.power)
+    }
+
+    /// How many pixels of the screen fit into one inch
+    /// along its (unrotated) horizontal axis,
+    /// ignoring the scale.
+    /// [`None`] if either the resolution or physical size are unknown.
+    #[must_use]
+    pub fn dpi(&self) -> Option<f64> {
+        const MM_PER_INCH: f64 = 25.4;
+
+        let resolution = self.resolution?;
+        let physical = self.physical_size.filter(|physical| physical.width != 0)?;
+        Some(resolution.width as f64 / (physical.width as f64 / MM_PER_INCH))
+    }
 }
@@ -0,0 +1,71 @@
+//! Information WMs don't tell, but which can be read from the kernel directly
+//! if the WM sits on top of DRM anyway.
+//!
+//! Everything here is best-effort:
+//! If something can't be read, it's just [`None`].
+
+use std::{fs, path::PathBuf};
+
+use crate::geometry::PhysicalSize;
+
+use super::Port;
+
+const SYSFS_DRM: &str = "/sys/class/drm";
+
+/// Reads how large the screen at `port` physically is
+/// from its EDID.
+#[must_use]
+pub fn physical_size(port: Port) -> Option<PhysicalSize> {
+    let edid = fs::read(connector_dir(port)?.join("edid")).ok()?;
+    parse_physical_size(&edid)
+}
+
+/// Finds the sysfs directory of the given port,
+/// which is named like `card0-DP-1`.
+fn connector_dir(port: Port) -> Option<PathBuf> {
+    let name = port.to_string();
+
+    fs::read_dir(SYSFS_DRM)
+        .ok()?
+        .filter_map(Result::ok)
+        .find(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|dir| dir.strip_prefix("card"))
+                .and_then(|dir| dir.split_once('-'))
+                .is_some_and(|(_, connector)| connector == name)
+        })
+        .map(|entry| entry.path())
+}
+
+/// See the [EDID structure] for the offsets used.
+///
+/// [EDID structure]: https://en.wikipedia.org/wiki/Extended_Display_Identification_Data#EDID_1.4_data_format
+fn parse_physical_size(edid: &[u8]) -> Option<PhysicalSize> {
+    const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+    const FIRST_DETAILED_TIMING: usize = 54;
+
+    if edid.len() < 128 || edid[..8] != HEADER {
+        return None;
+    }
+
+    // the first detailed timing descriptor has the size in millimeters,
+    // but only if it's actually a timing descriptor (non-zero pixel clock)
+    let timing = &edid[FIRST_DETAILED_TIMING..FIRST_DETAILED_TIMING + 18];
+    let size = if timing[0] != 0 || timing[1] != 0 {
+        PhysicalSize {
+            width: u32::from(timing[12]) | u32::from(timing[14] >> 4) << 8,
+            height: u32::from(timing[13]) | u32::from(timing[14] & 0x0f) << 8,
+        }
+    } else {
+        // otherwise fall back to the basic parameters, which are only in centimeters
+        PhysicalSize {
+            width: u32::from(edid[21]) * 10,
+            height: u32::from(edid[22]) * 10,
+        }
+    };
+
+    // projectors and the likes report 0 since they don't have a fixed size
+    (size.width != 0 && size.height != 0).then_some(size)
+}
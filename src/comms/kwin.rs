@@ -0,0 +1,349 @@
+//! Comms with [KWin](https://invent.kde.org/plasma/kwin), the WM of KDE Plasma,
+//! by running `kscreen-doctor`.
+//!
+//! The current state is read from `kscreen-doctor -j`,
+//! layouts are applied by passing settings like `output.DP-1.position.0,0` to it.
+
+use std::{
+    io,
+    process::{Command, Output as ProcessOutput},
+};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    absolute::{self, Explicit, Mode, Output, OutputConfig},
+    geometry::{Interval, PhysicalSize, Rect, Rotation, Rounding, Size, Transform},
+    Map,
+};
+
+use super::{sway::ParsePortError, Port, Result};
+
+const PROGRAM: &str = "kscreen-doctor";
+
+pub fn establish() -> Result<Box<dyn super::Comms>> {
+    // make sure it's actually there before promising anything
+    run(&["--version"])?;
+    Ok(Box::new(Comms) as Box<dyn super::Comms>)
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Could not run `{PROGRAM}`: {0}")]
+    Spawn(io::Error),
+    #[error("`{PROGRAM}` failed: {stderr}")]
+    Failed { stderr: String },
+    #[error("Could not parse JSON from `{PROGRAM}`: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Could not parse output name `{raw}` into port: {err}")]
+    ParsePort { raw: String, err: ParsePortError },
+    #[error("Rotation `{raw}` is unknown")]
+    UnknownRotation { raw: u32 },
+    #[error("{port} is not known to KWin")]
+    UnknownPort { port: Port },
+    #[error("{port} can't be driven at {size}")]
+    NoMatchingMode { port: Port, size: Size },
+    #[error("KWin can't create outputs")]
+    CreateUnsupported,
+}
+
+#[derive(Debug)]
+pub struct Comms;
+
+impl super::Comms for Comms {
+    fn layout(&mut self) -> Result<absolute::Layout> {
+        let mut layout = state()?
+            .connected()
+            .map(|raw| {
+                Ok(Output {
+                    port: raw.port()?,
+                    cfg: raw.config()?,
+                })
+            })
+            .collect::<Result<absolute::Layout, Error>>()?;
+        // it's all kscreen-doctor told us about after all
+        layout.complete = true;
+
+        Ok(layout)
+    }
+
+    fn modes(&mut self) -> Result<Map<Port, Vec<Mode>>> {
+        let modes = state()?
+            .connected()
+            .map(|raw| {
+                let mut modes: Vec<_> = raw.modes.iter().collect();
+                // stable, so the rest stays in the order kscreen-doctor lists them
+                modes.sort_by_key(|mode| !raw.preferred_modes.contains(&mode.id));
+                Ok((
+                    raw.port()?,
+                    modes.into_iter().map(RawMode::to_mode).collect(),
+                ))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(modes)
+    }
+
+    fn set_layout(&mut self, layout: &absolute::Layout) -> Result<()> {
+        let state = state()?;
+
+        let mut settings = Vec::new();
+        for output in layout.outputs() {
+            if output.cfg.create {
+                return Err(Error::CreateUnsupported.into());
+            }
+
+            let raw = state
+                .connected()
+                .find(|raw| raw.port().is_ok_and(|port| port == *output.port))
+                .ok_or(Error::UnknownPort { port: *output.port })?;
+            settings.extend(raw.settings(*output.port, output.cfg)?);
+        }
+
+        if !settings.is_empty() {
+            run(&settings)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_layout_atomic(&mut self, layout: &absolute::Layout) -> Result<()> {
+        // kscreen-doctor applies all settings of one invocation together
+        self.set_layout(layout)
+    }
+}
+
+fn state() -> Result<State, Error> {
+    let out = run(&["-j"])?;
+    Ok(serde_json::from_slice(&out.stdout)?)
+}
+
+fn run<S: AsRef<std::ffi::OsStr>>(args: &[S]) -> Result<ProcessOutput, Error> {
+    let out = Command::new(PROGRAM)
+        .args(args)
+        .output()
+        .map_err(Error::Spawn)?;
+
+    if !out.status.success() {
+        return Err(Error::Failed {
+            stderr: String::from_utf8_lossy(&out.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(out)
+}
+
+/// What `kscreen-doctor -j` prints, as far as needed.
+#[derive(Debug, Deserialize)]
+struct State {
+    outputs: Vec<RawOutput>,
+}
+
+impl State {
+    /// Outputs which are physically there, unlike the ones only remembered.
+    fn connected(&self) -> impl Iterator<Item = &RawOutput> {
+        self.outputs.iter().filter(|raw| raw.connected)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawOutput {
+    /// Same as the DRM connector name, like sway does.
+    name: String,
+    connected: bool,
+    enabled: bool,
+    /// In logical pixels.
+    pos: RawPoint,
+    scale: f64,
+    rotation: u32,
+    // the following might be missing for disabled outputs
+    #[serde(default)]
+    current_mode_id: String,
+    #[serde(default)]
+    preferred_modes: Vec<String>,
+    #[serde(default)]
+    modes: Vec<RawMode>,
+    #[serde(rename = "sizeMM")]
+    size_mm: Option<RawSize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMode {
+    id: String,
+    refresh_rate: f64,
+    size: RawSize,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+struct RawPoint {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+struct RawSize {
+    width: i32,
+    height: i32,
+}
+
+impl RawOutput {
+    fn port(&self) -> Result<Port, Error> {
+        Port::parse_from_sway(&self.name).map_err(|err| Error::ParsePort {
+            raw: self.name.clone(),
+            err,
+        })
+    }
+
+    fn config(&self) -> Result<OutputConfig, Error> {
+        let transform = Transform::from_kwin(self.rotation)?;
+        let physical_size = self
+            .size_mm
+            .filter(|size| size.width > 0 && size.height > 0)
+            .map(|size| PhysicalSize {
+                width: size.width.unsigned_abs(),
+                height: size.height.unsigned_abs(),
+            });
+
+        let current = self
+            .modes
+            .iter()
+            .find(|mode| mode.id == self.current_mode_id)
+            .map(RawMode::to_mode);
+        let Some(mode) = current.filter(|_| self.enabled) else {
+            return Ok(OutputConfig {
+                physical_size,
+                ..OutputConfig::default()
+            });
+        };
+
+        let size = mode
+            .size
+            .rotate(transform.rotation)
+            .scaled_logical(self.scale, Rounding::default());
+
+        Ok(OutputConfig {
+            bounds: Rect {
+                x: Interval::new(self.pos.x, self.pos.x + size.width),
+                y: Interval::new(self.pos.y, self.pos.y + size.height),
+            },
+            resolution: Some(mode.size),
+            refresh: Some(mode.refresh),
+            scale: Some(self.scale),
+            transform,
+            active: true,
+            physical_size,
+            // nothing to set, it's already the state of the WM
+            explicit: Explicit::default(),
+            ..OutputConfig::default()
+        })
+    }
+
+    /// Arguments for `kscreen-doctor` to configure this output like `cfg`.
+    /// Like for sway, only the fields selected by [`OutputConfig::explicit`] are included.
+    fn settings(&self, port: Port, cfg: &OutputConfig) -> Result<Vec<String>, Error> {
+        let prefix = format!("output.{}", self.name);
+        let mut settings = Vec::new();
+
+        if !cfg.active {
+            if cfg.explicit.active {
+                settings.push(format!("{prefix}.disable"));
+            }
+            return Ok(settings);
+        }
+        settings.push(format!("{prefix}.enable"));
+
+        if cfg.explicit.position {
+            let (x, y) = (cfg.bounds.x.start(), cfg.bounds.y.start());
+            settings.push(format!("{prefix}.position.{x},{y}"));
+        }
+
+        if let Some(size) = cfg.resolution.filter(|_| cfg.explicit.resolution) {
+            let id = self
+                .mode_id(size, cfg.refresh)
+                .ok_or(Error::NoMatchingMode { port, size })?;
+            settings.push(format!("{prefix}.mode.{id}"));
+        }
+
+        if let Some(scale) = cfg.scale.filter(|_| cfg.explicit.scale) {
+            settings.push(format!("{prefix}.scale.{scale}"));
+        }
+
+        if cfg.explicit.transform {
+            settings.push(format!("{prefix}.rotation.{}", cfg.transform.to_kwin()));
+        }
+
+        Ok(settings)
+    }
+
+    /// The ID of the mode with the given size and the closest refresh rate.
+    /// Without refresh rate, the preferred one of that size or the fastest one.
+    fn mode_id(&self, size: Size, refresh: Option<f64>) -> Option<&str> {
+        let preferred = |mode: &&RawMode| self.preferred_modes.contains(&mode.id);
+        let candidates = self.modes.iter().filter(|mode| mode.to_mode().size == size);
+
+        let best = match refresh {
+            Some(hz) => candidates.min_by(|a, b| {
+                (a.refresh_rate - hz)
+                    .abs()
+                    .total_cmp(&(b.refresh_rate - hz).abs())
+            }),
+            None => candidates.max_by(|a, b| {
+                preferred(a)
+                    .cmp(&preferred(b))
+                    .then_with(|| a.refresh_rate.total_cmp(&b.refresh_rate))
+            }),
+        };
+
+        best.map(|mode| mode.id.as_str())
+    }
+}
+
+impl RawMode {
+    fn to_mode(&self) -> Mode {
+        Mode {
+            size: Size {
+                width: self.size.width,
+                height: self.size.height,
+            },
+            refresh: self.refresh_rate,
+        }
+    }
+}
+
+impl Transform {
+    /// `KScreen` reports rotations as bit flags,
+    /// where `left` is counterclockwise and `right` clockwise.
+    pub fn from_kwin(raw: u32) -> Result<Self, Error> {
+        let (flipped, rotation) = match raw {
+            1 => (false, Rotation::None),
+            2 => (false, Rotation::ThreeQuarter),
+            4 => (false, Rotation::Half),
+            8 => (false, Rotation::Quarter),
+            16 => (true, Rotation::None),
+            32 => (true, Rotation::Quarter),
+            64 => (true, Rotation::Half),
+            128 => (true, Rotation::ThreeQuarter),
+            _ => return Err(Error::UnknownRotation { raw }),
+        };
+
+        Ok(Self { flipped, rotation })
+    }
+
+    /// How `kscreen-doctor` expects the rotation to be specified.
+    #[must_use]
+    pub fn to_kwin(&self) -> &'static str {
+        match (self.flipped, self.rotation) {
+            (false, Rotation::None) => "none",
+            (false, Rotation::Quarter) => "right",
+            (false, Rotation::Half) => "inverted",
+            (false, Rotation::ThreeQuarter) => "left",
+            (true, Rotation::None) => "flipped",
+            (true, Rotation::Quarter) => "flipped90",
+            (true, Rotation::Half) => "flipped180",
+            (true, Rotation::ThreeQuarter) => "flipped270",
+        }
+    }
+}
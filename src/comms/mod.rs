@@ -2,7 +2,10 @@
 //! to learn about available screens
 //! and apply the calculated ones.
 //!
-//! Only comms with [Sway](https://swaywm.org/) via [`swayipc`] are implemented.
+//! Comms with [Sway](https://swaywm.org/) via [`swayipc`],
+//! with Mutter, the WM of GNOME, via D-Bus (behind the `mutter` feature),
+//! with `KWin`, the WM of KDE Plasma, via `kscreen-doctor`
+//! and with niri via its IPC socket are implemented.
 //! Support for other WMs can be added via:
 //!
 //! 1. Adding a new submodule named after the WM, henceforth called `a`
@@ -10,34 +13,62 @@
 //! 3. Building that struct in [`establish`]
 //!    if there are signs present that the WM is running
 //!    in the current session
+//!
+//! If no WM is running at all,
+//! [`offline::Offline`] can stand in as long as nothing is applied.
 
+pub mod drm;
+pub mod kwin;
+#[cfg(feature = "mutter")]
+pub mod mutter;
+pub mod niri;
+pub mod offline;
 pub mod sway;
 
 use std::{env, fmt};
 
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
-use crate::{absolute, info::Connector};
+use crate::{absolute, info::Connector, Map};
 
 pub type Name = String;
 
 /// Figure out what WM we're running on and
-pub fn establish() -> Result<Box<dyn Comms>, Error> {
-    let comms = if env::var("SWAYSOCK").is_ok() {
-        sway::establish()?
-    } else {
-        return Err(Error::NoWmRunning);
-    };
-
-    Ok(comms)
+/// connect to it, calling outputs like in `names` where the WM supports that.
+pub fn establish(names: &CustomNames) -> Result<Box<dyn Comms>, Error> {
+    if env::var("SWAYSOCK").is_ok() {
+        return sway::establish(names.clone());
+    }
+    if env::var("NIRI_SOCKET").is_ok() {
+        return niri::establish();
+    }
+    #[cfg(feature = "mutter")]
+    if env::var("XDG_CURRENT_DESKTOP").is_ok_and(|desktop| desktop.contains("GNOME")) {
+        return mutter::establish();
+    }
+    if env::var("KDE_FULL_SESSION").is_ok() || env::var("KDE_SESSION_VERSION").is_ok() {
+        return kwin::establish();
+    }
+
+    Err(Error::NoWmRunning)
 }
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("When communicating with sway: {0}")]
     Sway(#[from] sway::Error),
+    #[cfg(feature = "mutter")]
+    #[error("When communicating with Mutter: {0}")]
+    Mutter(#[from] mutter::Error),
+    #[error("When communicating with KWin: {0}")]
+    Kwin(#[from] kwin::Error),
+    #[error("When communicating with niri: {0}")]
+    Niri(#[from] niri::Error),
     #[error("No known WM is running")]
     NoWmRunning,
+    #[error("No WM is running, so there's nothing to apply the layout to")]
+    Offline,
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -47,6 +78,23 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 pub trait Comms {
     fn layout(&mut self) -> Result<absolute::Layout>;
     fn set_layout(&mut self, layout: &absolute::Layout) -> Result<()>;
+
+    /// Sets the whole layout in one go,
+    /// so the WM never sees only part of it applied.
+    ///
+    /// By default, this falls back to [`Comms::set_layout`],
+    /// which might send one command per output.
+    fn set_layout_atomic(&mut self, layout: &absolute::Layout) -> Result<()> {
+        self.set_layout(layout)
+    }
+
+    /// All modes each output supports.
+    /// If known, the preferred mode of an output is listed first.
+    ///
+    /// By default, no modes are known at all.
+    fn modes(&mut self) -> Result<Map<Port, Vec<absolute::Mode>>> {
+        Ok(Map::new())
+    }
 }
 
 /// Where an output is plugged in.
@@ -58,8 +106,74 @@ pub struct Port {
     pub idx: u32,
 }
 
+impl Port {
+    /// How this port is referred to in the DSL.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{comms::Port, info::Connector};
+    /// assert_eq!(Port { kind: Connector::HdmiA, idx: 2 }.dsl_name(), "hdmia2");
+    /// assert_eq!(Port { kind: Connector::Edp, idx: 1 }.dsl_name(), "edp1");
+    /// ```
+    #[must_use]
+    pub fn dsl_name(&self) -> String {
+        format!("{}{}", self.kind.dsl_name(), self.idx)
+    }
+}
+
 impl fmt::Display for Port {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}-{}", self.kind, self.idx)
     }
 }
+
+/// Outputs the WM calls like one of the keys,
+/// treated as the port each maps to.
+/// An escape hatch for connectors layaway doesn't know yet.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{comms::{CustomNames, Port}, info::Connector};
+/// let hdmi = Port { kind: Connector::HdmiA, idx: 2 };
+/// let names = CustomNames([("FOO-1".to_string(), hdmi)].into());
+///
+/// assert_eq!(names.port("FOO-1"), Some(hdmi));
+/// assert_eq!(names.name_of(hdmi), "FOO-1");
+/// // all others are called like usual
+/// assert_eq!(names.name_of(Port { kind: Connector::HdmiA, idx: 1 }), "HDMI-A-1");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CustomNames(pub Map<Name, Port>);
+
+impl CustomNames {
+    /// The port for the output called `name`, if it's one of these.
+    #[must_use]
+    pub fn port(&self, name: &str) -> Option<Port> {
+        self.0.get(name).copied()
+    }
+
+    /// What the WM calls `port`,
+    /// so it gets back the name it uses.
+    #[must_use]
+    pub fn name_of(&self, port: Port) -> Name {
+        self.0
+            .iter()
+            .find(|(_, custom)| **custom == port)
+            .map_or_else(|| port.to_string(), |(name, _)| name.clone())
+    }
+}
+
+impl<'de> Deserialize<'de> for Port {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Self::parse_from_sway(&source).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Port {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
@@ -0,0 +1,484 @@
+//! Comms with [Mutter](https://gitlab.gnome.org/GNOME/mutter), the WM of GNOME,
+//! over its `org.gnome.Mutter.DisplayConfig` D-Bus interface.
+//!
+//! Mutter groups monitors into logical monitors,
+//! which share position, scale and transform.
+//! Each monitor is reported as its own output
+//! with the bounds of its logical monitor.
+//! When applying, outputs with the same position, scale and transform
+//! are grouped into one logical monitor again, i.e. mirrored.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+use zbus::{
+    blocking::{Connection, Proxy},
+    zvariant::OwnedValue,
+};
+
+use crate::{
+    absolute::{self, Explicit, Identity, Mode, Output, OutputConfig},
+    geometry::{Interval, Point, Rect, Rotation, Rounding, Size, Transform},
+    Map,
+};
+
+use super::{sway::ParsePortError, Port, Result};
+
+const DESTINATION: &str = "org.gnome.Mutter.DisplayConfig";
+const PATH: &str = "/org/gnome/Mutter/DisplayConfig";
+
+/// Apply the configuration without asking the user to confirm it,
+/// and without storing it for the next session,
+/// just like sway does.
+const METHOD_TEMPORARY: u32 = 1;
+/// Positions are in logical pixels, i.e. already divided by the scale.
+const LAYOUT_MODE_LOGICAL: u32 = 1;
+
+type Props = HashMap<String, OwnedValue>;
+/// Connector, vendor, product, serial.
+type MonitorSpec = (String, String, String, String);
+/// ID, width, height, refresh rate, preferred scale, supported scales, properties.
+type RawMode = (String, i32, i32, f64, f64, Vec<f64>, Props);
+/// Spec, modes, properties.
+type RawMonitor = (MonitorSpec, Vec<RawMode>, Props);
+/// X, Y, scale, transform, if primary, monitors, properties.
+type RawLogicalMonitor = (i32, i32, f64, u32, bool, Vec<MonitorSpec>, Props);
+/// Serial, monitors, logical monitors, properties.
+type RawState = (u32, Vec<RawMonitor>, Vec<RawLogicalMonitor>, Props);
+
+/// Connector, mode ID, properties.
+type MonitorConfig = (String, String, Props);
+/// X, Y, scale, transform, if primary, monitors.
+type LogicalMonitorConfig = (i32, i32, f64, u32, bool, Vec<MonitorConfig>);
+
+pub fn establish() -> Result<Box<dyn super::Comms>> {
+    let conn = Connection::session().map_err(Error::DBus)?;
+    Ok(Box::new(Comms { conn }) as Box<dyn super::Comms>)
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Over D-Bus: {0}")]
+    DBus(#[from] zbus::Error),
+    #[error("Could not parse connector name `{raw}` into port: {err}")]
+    ParsePort { raw: String, err: ParsePortError },
+    #[error("Transform `{raw}` is unknown, was not in 0..=7")]
+    UnknownTransform { raw: u32 },
+    #[error("Mutter places screens in physical pixels, but only logical pixels are supported for applying layouts")]
+    PhysicalLayoutMode,
+    #[error("{port} is not known to Mutter")]
+    UnknownPort { port: Port },
+    #[error("{port} has no modes it could be driven at")]
+    NoModes { port: Port },
+    #[error("{port} can't be driven at {size}")]
+    NoMatchingMode { port: Port, size: Size },
+    #[error("Mutter can't create outputs")]
+    CreateUnsupported,
+}
+
+#[derive(Debug)]
+pub struct Comms {
+    pub conn: Connection,
+}
+
+impl super::Comms for Comms {
+    fn layout(&mut self) -> Result<absolute::Layout> {
+        let mut layout = self.state()?.layout()?;
+
+        // mutter doesn't tell us, so ask the kernel instead
+        for (port, cfg) in &mut layout.outputs {
+            cfg.physical_size = super::drm::physical_size(*port);
+        }
+
+        Ok(layout)
+    }
+
+    fn modes(&mut self) -> Result<Map<Port, Vec<Mode>>> {
+        let modes = self
+            .state()?
+            .monitors
+            .into_iter()
+            .map(|(port, monitor)| {
+                let mut modes = monitor.modes;
+                // stable, so the rest stays in the order mutter lists them
+                modes.sort_by_key(|mode| !flag(&mode.6, "is-preferred"));
+                (port, modes.iter().map(to_mode).collect())
+            })
+            .collect();
+
+        Ok(modes)
+    }
+
+    fn set_layout(&mut self, layout: &absolute::Layout) -> Result<()> {
+        let state = self.state()?;
+        if state.layout_mode != LAYOUT_MODE_LOGICAL {
+            return Err(Error::PhysicalLayoutMode.into());
+        }
+        let current = state.layout()?;
+
+        // mutter disables everything that's not in the new configuration,
+        // so the outputs not in the layout have to be kept as they are
+        let mut targets = Vec::new();
+        for (port, old) in &current.outputs {
+            if old.active && !layout.outputs.contains_key(port) {
+                targets.push(state.target(*port, old, old)?);
+            }
+        }
+        for output in layout.outputs() {
+            if output.cfg.create {
+                return Err(Error::CreateUnsupported.into());
+            }
+
+            let old = current
+                .outputs
+                .get(output.port)
+                .ok_or(Error::UnknownPort { port: *output.port })?;
+            if output.cfg.active {
+                targets.push(state.target(*output.port, old, output.cfg)?);
+            } else if old.active && !output.cfg.explicit.active {
+                // leaving it out would disable it, which wasn't asked for
+                targets.push(state.target(*output.port, old, old)?);
+            }
+        }
+
+        let mut logical_monitors = group(targets);
+        // mutter insists on exactly one primary logical monitor
+        let primary = logical_monitors
+            .iter()
+            .position(|logical| {
+                logical
+                    .5
+                    .iter()
+                    .any(|(connector, ..)| state.primary.contains(connector))
+            })
+            .unwrap_or_default();
+        if let Some(logical) = logical_monitors.get_mut(primary) {
+            logical.4 = true;
+        }
+
+        self.proxy()?
+            .call_method(
+                "ApplyMonitorsConfig",
+                &(
+                    state.serial,
+                    METHOD_TEMPORARY,
+                    logical_monitors,
+                    Props::new(),
+                ),
+            )
+            .map_err(Error::DBus)?;
+
+        Ok(())
+    }
+
+    fn set_layout_atomic(&mut self, layout: &absolute::Layout) -> Result<()> {
+        // ApplyMonitorsConfig already takes the entire configuration at once
+        self.set_layout(layout)
+    }
+}
+
+impl Comms {
+    fn proxy(&self) -> Result<Proxy<'_>, Error> {
+        Ok(Proxy::new(&self.conn, DESTINATION, PATH, DESTINATION)?)
+    }
+
+    fn state(&self) -> Result<State, Error> {
+        let (serial, monitors, logical_monitors, props): RawState =
+            self.proxy()?.call("GetCurrentState", &())?;
+
+        let monitors = monitors
+            .into_iter()
+            .map(|((connector, vendor, product, serial), modes, _)| {
+                let port = parse_port(&connector)?;
+                let identity = Identity {
+                    make: vendor,
+                    model: product,
+                    serial: Some(serial).filter(|serial| !serial.is_empty()),
+                };
+                Ok((
+                    port,
+                    Monitor {
+                        connector,
+                        identity,
+                        modes,
+                    },
+                ))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let primary = logical_monitors
+            .iter()
+            .filter(|logical| logical.4)
+            .flat_map(|logical| logical.5.iter().map(|spec| spec.0.clone()))
+            .collect();
+
+        Ok(State {
+            serial,
+            monitors,
+            logical_monitors,
+            primary,
+            layout_mode: props
+                .get("layout-mode")
+                .and_then(|mode| u32::try_from(mode).ok())
+                .unwrap_or(LAYOUT_MODE_LOGICAL),
+        })
+    }
+}
+
+/// What mutter currently knows about.
+struct State {
+    /// Has to be passed back when applying,
+    /// so mutter can tell if the state changed in the meantime.
+    serial: u32,
+    monitors: Map<Port, Monitor>,
+    logical_monitors: Vec<RawLogicalMonitor>,
+    /// Connectors of the monitors in the primary logical monitor.
+    primary: Vec<String>,
+    layout_mode: u32,
+}
+
+struct Monitor {
+    /// As mutter calls it, which is not always the same as sway.
+    connector: String,
+    identity: Identity,
+    modes: Vec<RawMode>,
+}
+
+/// How one monitor is supposed to be configured.
+struct Target {
+    connector: String,
+    mode: String,
+    pos: Point,
+    scale: f64,
+    transform: Transform,
+}
+
+impl State {
+    fn layout(&self) -> Result<absolute::Layout, Error> {
+        let mut layout = self
+            .monitors
+            .iter()
+            .map(|(port, monitor)| {
+                Ok(Output {
+                    port: *port,
+                    cfg: OutputConfig {
+                        identity: Some(monitor.identity.clone()),
+                        ..self.output_config(monitor)?
+                    },
+                })
+            })
+            .collect::<Result<absolute::Layout, Error>>()?;
+        // it's all mutter told us about after all
+        layout.complete = true;
+
+        Ok(layout)
+    }
+
+    fn output_config(&self, monitor: &Monitor) -> Result<OutputConfig, Error> {
+        let logical = self.logical_monitors.iter().find(|logical| {
+            logical
+                .5
+                .iter()
+                .any(|(connector, ..)| *connector == monitor.connector)
+        });
+        let current = monitor
+            .modes
+            .iter()
+            .find(|mode| flag(&mode.6, "is-current"));
+
+        let (Some(&(x, y, scale, transform, ..)), Some(mode)) = (logical, current) else {
+            // not part of any logical monitor means it's off
+            return Ok(OutputConfig::default());
+        };
+
+        let transform = Transform::from_mutter(transform)?;
+        let mode = to_mode(mode);
+        let mut size = mode.size.rotate(transform.rotation);
+        if self.layout_mode == LAYOUT_MODE_LOGICAL {
+            size = size.scaled_logical(scale, Rounding::default());
+        }
+
+        Ok(OutputConfig {
+            bounds: Rect {
+                x: Interval::new(x, x + size.width),
+                y: Interval::new(y, y + size.height),
+            },
+            resolution: Some(mode.size),
+            refresh: Some(mode.refresh),
+            scale: Some(scale),
+            transform,
+            active: true,
+            // nothing to set, it's already the state of the WM
+            explicit: Explicit::default(),
+            ..OutputConfig::default()
+        })
+    }
+
+    /// Mutter always takes the whole configuration at once,
+    /// so everything `new` doesn't select explicitly is taken from `old`.
+    fn target(&self, port: Port, old: &OutputConfig, new: &OutputConfig) -> Result<Target, Error> {
+        let monitor = self
+            .monitors
+            .get(&port)
+            .ok_or(Error::UnknownPort { port })?;
+        let explicit = new.explicit;
+        // if it's off, there's nothing to keep in the first place
+        let pick = |select: bool| if select || !old.active { new } else { old };
+
+        let bounds = pick(explicit.position).bounds;
+        let resolution = pick(explicit.resolution);
+        Ok(Target {
+            connector: monitor.connector.clone(),
+            mode: monitor.mode_id(port, resolution.resolution, resolution.refresh)?,
+            pos: Point {
+                x: bounds.x.start(),
+                y: bounds.y.start(),
+            },
+            // mutter needs one either way
+            scale: pick(explicit.scale).scale.unwrap_or(1.0),
+            transform: pick(explicit.transform).transform,
+        })
+    }
+}
+
+impl Monitor {
+    /// The ID of the mode with the given size and the closest refresh rate.
+    /// Without size, the preferred mode is used.
+    /// Without refresh rate, the preferred one of that size or the fastest one.
+    fn mode_id(
+        &self,
+        port: Port,
+        size: Option<Size>,
+        refresh: Option<f64>,
+    ) -> Result<String, Error> {
+        let preferred = |mode: &&RawMode| flag(&mode.6, "is-preferred");
+
+        let Some(size) = size else {
+            return self
+                .modes
+                .iter()
+                .find(preferred)
+                .or_else(|| self.modes.first())
+                .map(|mode| mode.0.clone())
+                .ok_or(Error::NoModes { port });
+        };
+
+        let candidates = self.modes.iter().filter(|mode| to_mode(mode).size == size);
+        let best = match refresh {
+            Some(hz) => candidates.min_by(|a, b| (a.3 - hz).abs().total_cmp(&(b.3 - hz).abs())),
+            None => candidates.max_by(|a, b| {
+                preferred(a)
+                    .cmp(&preferred(b))
+                    .then_with(|| a.3.total_cmp(&b.3))
+            }),
+        };
+
+        best.map(|mode| mode.0.clone())
+            .ok_or(Error::NoMatchingMode { port, size })
+    }
+}
+
+/// Puts targets with the same position, scale and transform
+/// into the same logical monitor, so they mirror each other.
+#[allow(clippy::float_cmp)] // only exactly the same scale is meant
+fn group(targets: Vec<Target>) -> Vec<LogicalMonitorConfig> {
+    let mut logical_monitors: Vec<LogicalMonitorConfig> = Vec::new();
+
+    for target in targets {
+        let transform = target.transform.to_mutter();
+        let monitor = (target.connector, target.mode, Props::new());
+
+        let existing = logical_monitors.iter_mut().find(|logical| {
+            (logical.0, logical.1, logical.3) == (target.pos.x, target.pos.y, transform)
+                && logical.2 == target.scale
+        });
+        if let Some(logical) = existing {
+            logical.5.push(monitor);
+        } else {
+            logical_monitors.push((
+                target.pos.x,
+                target.pos.y,
+                target.scale,
+                transform,
+                false,
+                vec![monitor],
+            ));
+        }
+    }
+
+    logical_monitors
+}
+
+/// If the boolean property `name` is set and true.
+fn flag(props: &Props, name: &str) -> bool {
+    props
+        .get(name)
+        .and_then(|value| bool::try_from(value).ok())
+        .unwrap_or(false)
+}
+
+fn to_mode(raw: &RawMode) -> Mode {
+    Mode {
+        size: Size {
+            width: raw.1,
+            height: raw.2,
+        },
+        refresh: raw.3,
+    }
+}
+
+/// Mutter calls HDMI-A connectors just `HDMI`,
+/// otherwise it's the same as sway.
+fn parse_port(connector: &str) -> Result<Port, Error> {
+    let name = match connector.strip_prefix("HDMI-") {
+        Some(rest) if rest.parse::<u32>().is_ok() => format!("HDMI-A-{rest}"),
+        _ => connector.to_string(),
+    };
+
+    Port::parse_from_sway(&name).map_err(|err| Error::ParsePort {
+        raw: connector.to_string(),
+        err,
+    })
+}
+
+impl Transform {
+    /// Mutter uses the same numbering as `wl_output`:
+    /// 0 to 3 are the rotations in quarters,
+    /// 4 to 7 the same but flipped.
+    pub fn from_mutter(raw: u32) -> Result<Self, Error> {
+        let rotation = match raw % 4 {
+            0 => Rotation::None,
+            1 => Rotation::Quarter,
+            2 => Rotation::Half,
+            _ => Rotation::ThreeQuarter,
+        };
+
+        match raw {
+            0..=3 => Ok(Self {
+                flipped: false,
+                rotation,
+            }),
+            4..=7 => Ok(Self {
+                flipped: true,
+                rotation,
+            }),
+            _ => Err(Error::UnknownTransform { raw }),
+        }
+    }
+
+    #[must_use]
+    pub fn to_mutter(&self) -> u32 {
+        let rotation = match self.rotation {
+            Rotation::None => 0,
+            Rotation::Quarter => 1,
+            Rotation::Half => 2,
+            Rotation::ThreeQuarter => 3,
+        };
+
+        if self.flipped {
+            rotation + 4
+        } else {
+            rotation
+        }
+    }
+}
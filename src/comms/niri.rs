@@ -0,0 +1,333 @@
+//! Comms with [niri](https://github.com/YaLTeR/niri)
+//! over its IPC socket at `$NIRI_SOCKET`.
+//!
+//! Each request is one line of JSON on a fresh connection,
+//! answered by one line of JSON.
+
+use std::{
+    env,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    path::PathBuf,
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::{
+    absolute::{self, Explicit, Identity, Mode, Output, OutputConfig},
+    geometry::{Interval, PhysicalSize, Rect, Rotation, Size, Transform},
+    Map,
+};
+
+use super::{sway::ParsePortError, Port, Result};
+
+pub fn establish() -> Result<Box<dyn super::Comms>> {
+    let socket = env::var_os("NIRI_SOCKET")
+        .map(PathBuf::from)
+        .ok_or(Error::NoSocket)?;
+    Ok(Box::new(Comms { socket }) as Box<dyn super::Comms>)
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("`NIRI_SOCKET` is not set")]
+    NoSocket,
+    #[error("Over IPC: {0}")]
+    Io(#[from] io::Error),
+    #[error("Could not (de)serialize IPC message: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("niri refused: {0}")]
+    Refused(String),
+    #[error("Could not parse output name `{raw}` into port: {err}")]
+    ParsePort { raw: String, err: ParsePortError },
+    #[error("Transform `{raw}` is unknown")]
+    UnknownTransform { raw: String },
+    #[error("niri can't create outputs")]
+    CreateUnsupported,
+}
+
+#[derive(Debug)]
+pub struct Comms {
+    pub socket: PathBuf,
+}
+
+impl super::Comms for Comms {
+    fn layout(&mut self) -> Result<absolute::Layout> {
+        let mut layout = self
+            .outputs()?
+            .into_iter()
+            .map(|(name, raw)| {
+                Ok(Output {
+                    port: parse_port(&name)?,
+                    cfg: raw.config()?,
+                })
+            })
+            .collect::<Result<absolute::Layout, Error>>()?;
+        // it's all niri told us about after all
+        layout.complete = true;
+
+        Ok(layout)
+    }
+
+    fn modes(&mut self) -> Result<Map<Port, Vec<Mode>>> {
+        let modes = self
+            .outputs()?
+            .into_iter()
+            .map(|(name, raw)| {
+                let mut modes = raw.modes;
+                // stable, so the rest stays in the order niri lists them
+                modes.sort_by_key(|mode| !mode.is_preferred);
+                Ok((
+                    parse_port(&name)?,
+                    modes.iter().map(RawMode::to_mode).collect(),
+                ))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(modes)
+    }
+
+    fn set_layout(&mut self, layout: &absolute::Layout) -> Result<()> {
+        for output in layout.outputs() {
+            if output.cfg.create {
+                return Err(Error::CreateUnsupported.into());
+            }
+
+            for action in output.to_niri_actions() {
+                let request = json!({
+                    "Output": {
+                        "output": output.port.to_string(),
+                        "action": action,
+                    }
+                });
+                self.request::<Value>(&request)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Comms {
+    fn outputs(&self) -> Result<Map<String, RawOutput>, Error> {
+        let Response::Outputs(outputs) = self.request(&json!("Outputs"))?;
+        Ok(outputs)
+    }
+
+    fn request<T: DeserializeOwned>(&self, request: &Value) -> Result<T, Error> {
+        let mut stream = UnixStream::connect(&self.socket)?;
+        writeln!(stream, "{request}")?;
+        stream.flush()?;
+
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line)?;
+
+        match serde_json::from_str(&line)? {
+            Reply::Ok(reply) => Ok(reply),
+            Reply::Err(msg) => Err(Error::Refused(msg)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+enum Reply<T> {
+    Ok(T),
+    Err(String),
+}
+
+#[derive(Debug, Deserialize)]
+enum Response {
+    Outputs(Map<String, RawOutput>),
+}
+
+#[derive(Debug, Deserialize)]
+struct RawOutput {
+    make: String,
+    model: String,
+    serial: Option<String>,
+    /// Width and height in millimeters.
+    physical_size: Option<(u32, u32)>,
+    modes: Vec<RawMode>,
+    /// Index into `modes`.
+    current_mode: Option<usize>,
+    /// [`None`] if the output is off.
+    logical: Option<RawLogical>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawMode {
+    width: u16,
+    height: u16,
+    /// In mHz.
+    refresh_rate: u32,
+    is_preferred: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawLogical {
+    x: i32,
+    y: i32,
+    /// Already divided by the scale and rotated.
+    width: u32,
+    height: u32,
+    scale: f64,
+    transform: String,
+}
+
+impl RawOutput {
+    #[allow(clippy::cast_possible_wrap)] // logical sizes are nowhere near that large
+    fn config(&self) -> Result<OutputConfig, Error> {
+        let physical_size = self
+            .physical_size
+            .filter(|&(width, height)| width != 0 && height != 0)
+            .map(|(width, height)| PhysicalSize { width, height });
+
+        let identity = Some(Identity {
+            make: self.make.clone(),
+            model: self.model.clone(),
+            serial: self.serial.clone(),
+        });
+
+        let mode = self.current_mode.and_then(|idx| self.modes.get(idx));
+        let (Some(logical), Some(mode)) = (&self.logical, mode) else {
+            return Ok(OutputConfig {
+                physical_size,
+                identity,
+                ..OutputConfig::default()
+            });
+        };
+        let mode = mode.to_mode();
+
+        Ok(OutputConfig {
+            bounds: Rect {
+                x: Interval::new(logical.x, logical.x + logical.width as i32),
+                y: Interval::new(logical.y, logical.y + logical.height as i32),
+            },
+            resolution: Some(mode.size),
+            refresh: Some(mode.refresh),
+            scale: Some(logical.scale),
+            transform: Transform::parse_from_niri(&logical.transform)?,
+            active: true,
+            physical_size,
+            identity,
+            // nothing to set, it's already the state of the WM
+            explicit: Explicit::default(),
+            ..OutputConfig::default()
+        })
+    }
+}
+
+impl RawMode {
+    fn to_mode(&self) -> Mode {
+        Mode {
+            size: Size {
+                width: self.width.into(),
+                height: self.height.into(),
+            },
+            refresh: f64::from(self.refresh_rate) / 1000.0,
+        }
+    }
+}
+
+impl absolute::OutputRef<'_> {
+    /// Like for sway, only includes the fields selected by [`OutputConfig::explicit`].
+    /// Inactive outputs are left alone, unless [`Explicit::active`] is set.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // niri only knows u16 sizes
+    #[must_use]
+    pub fn to_niri_actions(&self) -> Vec<Value> {
+        let cfg = self.cfg;
+        let mut actions = Vec::new();
+
+        if !cfg.active {
+            if cfg.explicit.active {
+                actions.push(json!("Off"));
+            }
+            return actions;
+        }
+        actions.push(json!("On"));
+
+        if let Some(res) = cfg.resolution.filter(|_| cfg.explicit.resolution) {
+            actions.push(json!({
+                "Mode": {
+                    "mode": {
+                        "Specific": {
+                            "width": res.width as u16,
+                            "height": res.height as u16,
+                            "refresh": cfg.refresh,
+                        }
+                    }
+                }
+            }));
+        }
+
+        if let Some(scale) = cfg.scale.filter(|_| cfg.explicit.scale) {
+            actions.push(json!({ "Scale": { "scale": { "Specific": scale } } }));
+        }
+
+        if cfg.explicit.transform {
+            actions.push(json!({ "Transform": { "transform": cfg.transform.to_niri() } }));
+        }
+
+        if cfg.explicit.position {
+            actions.push(json!({
+                "Position": {
+                    "position": {
+                        "Specific": {
+                            "x": cfg.bounds.x.start(),
+                            "y": cfg.bounds.y.start(),
+                        }
+                    }
+                }
+            }));
+        }
+
+        actions
+    }
+}
+
+/// niri uses the same names as sway for outputs.
+fn parse_port(name: &str) -> Result<Port, Error> {
+    Port::parse_from_sway(name).map_err(|err| Error::ParsePort {
+        raw: name.to_string(),
+        err,
+    })
+}
+
+impl Transform {
+    /// niri rotates counterclockwise, while sway and hence layaway rotate clockwise.
+    pub fn parse_from_niri(raw: &str) -> Result<Self, Error> {
+        let (flipped, rotation) = match raw {
+            "Normal" => (false, Rotation::None),
+            "_90" => (false, Rotation::ThreeQuarter),
+            "_180" => (false, Rotation::Half),
+            "_270" => (false, Rotation::Quarter),
+            "Flipped" => (true, Rotation::None),
+            "Flipped90" => (true, Rotation::ThreeQuarter),
+            "Flipped180" => (true, Rotation::Half),
+            "Flipped270" => (true, Rotation::Quarter),
+            _ => {
+                return Err(Error::UnknownTransform {
+                    raw: raw.to_string(),
+                })
+            }
+        };
+
+        Ok(Self { flipped, rotation })
+    }
+
+    #[must_use]
+    pub fn to_niri(&self) -> &'static str {
+        match (self.flipped, self.rotation) {
+            (false, Rotation::None) => "Normal",
+            (false, Rotation::Quarter) => "_270",
+            (false, Rotation::Half) => "_180",
+            (false, Rotation::ThreeQuarter) => "_90",
+            (true, Rotation::None) => "Flipped",
+            (true, Rotation::Quarter) => "Flipped270",
+            (true, Rotation::Half) => "Flipped180",
+            (true, Rotation::ThreeQuarter) => "Flipped90",
+        }
+    }
+}
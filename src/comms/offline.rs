@@ -0,0 +1,48 @@
+//! Stand-in for when no WM is running at all,
+//! e.g. to print the configuration for a layout description
+//! on another machine than the one it's for.
+//!
+//! Knows only the outputs it's told about up front
+//! and never applies anything.
+
+use crate::absolute;
+
+use super::{Comms, Error, Result};
+
+/// Pretends that exactly the outputs in its layout are connected.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{comms::{offline::Offline, Comms}, relative};
+/// let mut comms = Offline::default();
+/// let relative: relative::Layout = "dp@4k + edp@1080p".parse()?;
+///
+/// // nothing is connected, but the resolutions are given anyway
+/// let converted = relative.to_absolute(&mut comms)?;
+/// assert_eq!(converted.layout.outputs.len(), 2);
+///
+/// assert!(comms.set_layout(&converted.layout).is_err());
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Offline {
+    layout: absolute::Layout,
+}
+
+impl Offline {
+    #[must_use]
+    pub fn new(layout: absolute::Layout) -> Self {
+        Self { layout }
+    }
+}
+
+impl Comms for Offline {
+    fn layout(&mut self) -> Result<absolute::Layout> {
+        Ok(self.layout.clone())
+    }
+
+    fn set_layout(&mut self, _: &absolute::Layout) -> Result<()> {
+        Err(Error::Offline)
+    }
+}
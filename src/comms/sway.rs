@@ -1,76 +1,341 @@
-use std::{fmt::Write, num::ParseIntError};
+use std::{env, fmt, fmt::Write, io, num::ParseIntError, path::PathBuf};
 
 use swayipc::Connection;
 use thiserror::Error;
 
 use crate::{
-    absolute::{self, Output, OutputConfig, OutputRef},
+    absolute::{
+        self, Background, Explicit, Identity, Mode, Output, OutputConfig, OutputRef, Subpixel,
+    },
     geometry::{Interval, Rect, Rotation, Size, Transform},
+    log, Map,
 };
 
-use super::{Port, Result};
+use super::{CustomNames, Port, Result};
+
+pub fn establish(names: CustomNames) -> Result<Box<dyn super::Comms>> {
+    let mut conn = Connection::new().map_err(connect_error)?;
+    let version = conn.get_version().map_err(Error::SwayIpc)?.into();
+    Ok(Box::new(Comms {
+        conn,
+        version,
+        names,
+    }) as Box<dyn super::Comms>)
+}
+
+/// Tells a `SWAYSOCK` pointing nowhere,
+/// e.g. since it lingered on from a sway that's long gone,
+/// apart from actual trouble with IPC.
+fn connect_error(err: swayipc::Error) -> Error {
+    let Some(path) = env::var_os("SWAYSOCK").map(PathBuf::from) else {
+        return err.into();
+    };
 
-pub fn establish() -> Result<Box<dyn super::Comms>> {
-    let conn = Connection::new().map_err(Error::SwayIpc)?;
-    Ok(Box::new(Comms { conn }) as Box<dyn super::Comms>)
+    match err {
+        _ if !path.exists() => Error::SocketMissing { path },
+        swayipc::Error::Io(io) if io.kind() == io::ErrorKind::ConnectionRefused => {
+            Error::SocketStale { path }
+        }
+        err => err.into(),
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Over IPC: {0}")]
     SwayIpc(#[from] swayipc::Error),
+    #[error("`SWAYSOCK` is set to `{}`, but there's no socket, is sway actually running?", .path.display())]
+    SocketMissing { path: PathBuf },
+    #[error("`SWAYSOCK` is set to `{}`, but nothing listens on it, is sway actually running?", .path.display())]
+    SocketStale { path: PathBuf },
     #[error("Could not parse output name `{raw}` into port: {err}")]
     ParsePort { raw: String, err: ParsePortError },
+    #[error("Created a headless output, but it did not show up")]
+    CreatedOutputMissing,
     #[error("Could not parse transform `{raw}`: {err}")]
     ParseTransform {
         raw: String,
         err: ParseTransformError,
     },
+    #[error("{}", describe_partial(.applied, .failed))]
+    Partial {
+        /// Outputs which were configured just fine.
+        applied: Vec<String>,
+        /// Outputs which couldn't be configured, and why.
+        failed: Vec<(String, Error)>,
+    },
+}
+
+/// Lists what went through and what didn't, one failure per line.
+fn describe_partial(applied: &[String], failed: &[(String, Error)]) -> String {
+    let mut desc = format!("Could not configure {} output(s)", failed.len());
+    if !applied.is_empty() {
+        write!(desc, " (applied to {} just fine)", applied.join(", ")).unwrap();
+    }
+    desc.push(':');
+    for (target, err) in failed {
+        write!(desc, "\n- {target}: {err}").unwrap();
+    }
+    desc
 }
 
 #[derive(Debug)]
 pub struct Comms {
     pub conn: Connection,
+    /// Of the running sway, so commands it doesn't know yet can be left out.
+    pub version: Version,
+    /// Outputs on connectors layaway doesn't know on its own.
+    pub names: CustomNames,
+}
+
+/// Release of sway.
+///
+/// Output attributes need at least these versions:
+///
+/// | Attribute          | Version |
+/// | ------------------ | ------- |
+/// | `adaptive_sync`    | 1.4     |
+/// | `max_render_time`  | 1.4     |
+/// | `subpixel`         | 1.4     |
+/// | `render_bit_depth` | 1.7     |
+/// | `power`            | 1.7     |
+///
+/// All others have been there since 1.0.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: i32,
+    pub minor: i32,
+    pub patch: i32,
+}
+
+impl Version {
+    /// Knows about everything, for when no sway to ask is around.
+    pub const LATEST: Self = Self {
+        major: i32::MAX,
+        minor: i32::MAX,
+        patch: i32::MAX,
+    };
+
+    const ADAPTIVE_SYNC: Self = Self::new(1, 4);
+    const MAX_RENDER_TIME: Self = Self::new(1, 4);
+    const SUBPIXEL: Self = Self::new(1, 4);
+    const RENDER_BIT_DEPTH: Self = Self::new(1, 7);
+    const POWER: Self = Self::new(1, 7);
+
+    const fn new(major: i32, minor: i32) -> Self {
+        Self {
+            major,
+            minor,
+            patch: 0,
+        }
+    }
+
+    /// If this version is at least `required`,
+    /// otherwise warns that `attribute` isn't sent to `target`.
+    fn supports(self, required: Self, attribute: &str, target: &str) -> bool {
+        let supported = self >= required;
+        if !supported {
+            log::warning!(
+                "sway {self} doesn't know `{attribute}` yet, it needs at least {required}, \
+                so not sending it for {target}"
+            );
+        }
+        supported
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl From<swayipc::Version> for Version {
+    fn from(raw: swayipc::Version) -> Self {
+        Self {
+            major: raw.major,
+            minor: raw.minor,
+            patch: raw.patch,
+        }
+    }
 }
 
 impl super::Comms for Comms {
     fn layout(&mut self) -> Result<absolute::Layout> {
         let outputs = self.conn.get_outputs().map_err(Error::SwayIpc)?;
-        let layout = outputs
+        let mut layout = outputs
             .into_iter()
-            .map(Output::try_from)
+            .map(|raw| Output::from_sway(raw, &self.names))
             .collect::<Result<absolute::Layout, Error>>()?;
+        // it's all sway told us about after all
+        layout.complete = true;
+
+        // sway doesn't tell us, so ask the kernel instead
+        for (port, cfg) in &mut layout.outputs {
+            cfg.physical_size = super::drm::physical_size(*port);
+        }
 
         Ok(layout)
     }
 
+    fn modes(&mut self) -> Result<Map<Port, Vec<Mode>>> {
+        let outputs = self.conn.get_outputs().map_err(Error::SwayIpc)?;
+        let modes = outputs
+            .into_iter()
+            .map(|raw| {
+                // sway lists them in the order the kernel does,
+                // which has the preferred one first
+                let modes = raw.modes.into_iter().map(Into::into).collect();
+                Ok((parse_port(&raw.name, &self.names)?, modes))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(modes)
+    }
+
+    /// Keeps going if configuring one output fails,
+    /// so the others still end up as in `layout`.
+    /// All failures are reported together at the end.
     fn set_layout(&mut self, layout: &absolute::Layout) -> Result<()> {
-        for cmd in layout.to_sway_commands() {
-            self.conn
-                .run_command(cmd)
-                // all below is just propagating errors, if any
-                .map_err(Error::SwayIpc)?
+        let shared = layout.shared();
+        let mut applied = Vec::new();
+        let mut failed = Vec::new();
+
+        let mut report = |target: String, result: Result<(), Error>| match result {
+            Ok(()) => applied.push(target),
+            Err(err) => failed.push((target, err)),
+        };
+
+        for output in layout.outputs() {
+            let target = self.names.name_of(*output.port);
+            report(target, self.apply_output(output, &shared));
+        }
+
+        // only set if it replaces all the per-output commands
+        if let Some(cmd) = shared.supported_by(self.version, "*").to_sway_command() {
+            report("*".to_string(), self.run(&cmd));
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Partial { applied, failed }.into())
+        }
+    }
+}
+
+impl Comms {
+    /// Sends everything for `output` except what's already in `shared`.
+    fn apply_output(&mut self, output: OutputRef<'_>, shared: &Shared) -> Result<(), Error> {
+        let cmd = if output.cfg.create {
+            // sway picks the name of created outputs itself,
+            // so look for whichever appeared and configure that one instead
+            let before = self.ports()?;
+            self.run("create_output")?;
+            let after = self.ports()?;
+
+            let port = after
                 .into_iter()
-                .collect::<Result<(), _>>()
-                .map_err(Error::SwayIpc)?;
+                .find(|port| !before.contains(port))
+                .ok_or(Error::CreatedOutputMissing)?;
+
+            OutputRef {
+                port: &port,
+                cfg: output.cfg,
+            }
+            .to_sway_command_except(shared, self.version, &self.names)
+        } else {
+            output.to_sway_command_except(shared, self.version, &self.names)
+        };
+
+        match cmd {
+            Some(cmd) => self.run(&cmd),
+            None => Ok(()),
         }
+    }
 
-        Ok(())
+    /// All ports sway currently knows about.
+    fn ports(&mut self) -> Result<Vec<Port>, Error> {
+        let outputs = self.conn.get_outputs()?;
+        outputs
+            .iter()
+            .map(|raw| parse_port(&raw.name, &self.names))
+            .collect()
+    }
+
+    fn run(&mut self, cmd: &str) -> Result<(), Error> {
+        self.conn
+            .run_command(cmd)
+            // all below is just propagating errors, if any
+            .map_err(Error::SwayIpc)?
+            .into_iter()
+            .collect::<Result<(), _>>()
+            .map_err(Error::SwayIpc)
     }
 }
 
+/// Reads everything sway reports about an output,
+/// so that sending it right back doesn't change anything.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{absolute::{Explicit, Layout, Output}, comms::CustomNames};
+/// let raw: swayipc::Output = serde_json::from_str(
+///     r#"{
+///         "id": 4,
+///         "name": "DP-1",
+///         "make": "Dell Inc.",
+///         "model": "DELL U2415",
+///         "serial": "7MT0186419YS",
+///         "active": true,
+///         "dpms": true,
+///         "power": true,
+///         "primary": false,
+///         "scale": 1.5,
+///         "subpixel_hinting": "rgb",
+///         "transform": "90",
+///         "adaptive_sync_status": "disabled",
+///         "max_render_time": 0,
+///         "current_workspace": "1",
+///         "modes": [{ "width": 1920, "height": 1200, "refresh": 59950 }],
+///         "current_mode": { "width": 1920, "height": 1200, "refresh": 59950 },
+///         "rect": { "x": 0, "y": 0, "width": 800, "height": 1280 },
+///         "focus": [],
+///         "focused": true
+///     }"#,
+/// )?;
+/// let read: Layout = [Output::try_from(raw)?].into_iter().collect();
+///
+/// let mut emitted = read.clone();
+/// emitted.outputs.values_mut().for_each(|cfg| cfg.explicit = Explicit::ALL);
+/// assert!(emitted.to_sway_commands(&CustomNames::default()).next().is_some());
+/// assert!(emitted.is_in_effect(&read));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
 impl TryFrom<swayipc::Output> for Output {
     type Error = Error;
     fn try_from(raw: swayipc::Output) -> Result<Self, Self::Error> {
+        Self::from_sway(raw, &CustomNames::default())
+    }
+}
+
+impl Output {
+    /// Like [`Output::try_from`],
+    /// but outputs called like one of `names` are treated as the port it maps to.
+    pub fn from_sway(raw: swayipc::Output, names: &CustomNames) -> Result<Self, Error> {
         Ok(Self {
-            port: Port::parse_from_sway(&raw.name).map_err(|err| Error::ParsePort {
-                raw: raw.name.clone(),
-                err,
-            })?,
+            port: parse_port(&raw.name, names)?,
             cfg: OutputConfig {
                 bounds: raw.rect.into(),
                 resolution: raw.current_mode.map(Into::into),
-                scale: raw.scale.unwrap_or(1.0),
+                refresh: raw.current_mode.map(|mode| Mode::from(mode).refresh),
+                // inactive outputs are reported with a made up scale, if any
+                scale: raw.scale.filter(|&scale| raw.active && scale > 0.0),
+                // only the transform currently in effect,
+                // sway doesn't report whether the panel suggests one itself
+                // (and neither does the base EDID block read in `drm`),
+                // so there's no "suggested transform" to show in `--list-outputs`
                 transform: raw.transform.map_or(Ok(Transform::default()), |raw| {
                     Transform::parse_from_sway(&raw).map_err(|err| Error::ParseTransform {
                         raw: raw.to_string(),
@@ -78,23 +343,61 @@ impl TryFrom<swayipc::Output> for Output {
                     })
                 })?,
                 active: raw.active,
+                // not exposed over IPC either, sway doesn't even know a range
+                refresh_range: None,
+                // not exposed over IPC, so we can't know
+                max_render_time: None,
+                // sway reports `unknown` if the output doesn't tell,
+                // which can't be set and hence isn't a variant
+                subpixel: raw
+                    .subpixel_hinting
+                    .and_then(|subpixel| subpixel.parse().ok()),
+                // not exposed over IPC either
+                render_bit_depth: None,
+                // only meaningful if it's part of the layout at all
+                power: raw.active.then_some(raw.dpms),
+                // not exposed over IPC either
+                background: None,
+                physical_size: None,
+                identity: Some(Identity {
+                    make: raw.make,
+                    model: raw.model,
+                    // sway fills in `Unknown` if there's none
+                    serial: Some(raw.serial).filter(|serial| serial != "Unknown"),
+                }),
+                // it's there after all
+                create: false,
+                // nothing to set, it's already the state of the WM
+                explicit: Explicit::default(),
             },
         })
     }
 }
 
+/// Names in `names` take precedence.
+fn parse_port(name: &str, names: &CustomNames) -> Result<Port, Error> {
+    if let Some(port) = names.port(name) {
+        return Ok(port);
+    }
+
+    Port::parse_from_sway(name).map_err(|err| Error::ParsePort {
+        raw: name.to_string(),
+        err,
+    })
+}
+
 #[derive(Debug, Error)]
 pub enum ParsePortError {
     #[error("Output name must contain a dash to separate connector from index, but is `{name}`")]
     NoDash { name: String },
-    #[error("New unknown connector name `{connector}`, perhaps libDRM got updated with new connectors? Need to add them in source here then. Feel free to report this!")]
+    #[error("New unknown connector name `{connector}`, perhaps libDRM got updated with new connectors? Need to add them in source here then, or map the output to a known connector in `[connectors]` of the config file for now. Feel free to report this!")]
     NewConnector { connector: String },
     #[error("Port index `{idx}` is not an integer: {err}")]
     IdxNotANumber { idx: String, err: ParseIntError },
 }
 
 impl Port {
-    fn parse_from_sway(name: &str) -> Result<Self, ParsePortError> {
+    pub fn parse_from_sway(name: &str) -> Result<Self, ParsePortError> {
         let (kind, idx) = name
             .rsplit_once('-')
             .ok_or_else(|| ParsePortError::NoDash {
@@ -132,17 +435,13 @@ impl Transform {
             raw
         };
 
-        let rotation = match angle {
-            "0" => Rotation::None,
-            "90" => Rotation::Quarter,
-            "180" => Rotation::Half,
-            "270" => Rotation::ThreeQuarter,
-            _ => {
-                return Err(ParseTransformError::InvalidAngle {
-                    raw: raw.to_string(),
-                })
-            }
-        };
+        let rotation = angle
+            .parse()
+            .ok()
+            .and_then(Rotation::from_degrees)
+            .ok_or_else(|| ParseTransformError::InvalidAngle {
+                raw: raw.to_string(),
+            })?;
 
         Ok(Self { flipped, rotation })
     }
@@ -156,15 +455,12 @@ impl Transform {
         let mut parts = Vec::new();
 
         if self.flipped {
-            parts.push("flipped");
+            parts.push("flipped".to_string());
         }
 
-        match self.rotation {
-            Rotation::None => (),
-            Rotation::Quarter => parts.push("90"),
-            Rotation::Half => parts.push("180"),
-            Rotation::ThreeQuarter => parts.push("270"),
-        };
+        if self.rotation != Rotation::None {
+            parts.push(self.rotation.degrees().to_string());
+        }
 
         parts.join("-")
     }
@@ -193,40 +489,383 @@ impl From<swayipc::Mode> for Size {
     }
 }
 
+impl From<swayipc::Mode> for Mode {
+    fn from(raw: swayipc::Mode) -> Self {
+        Self {
+            size: raw.into(),
+            // sway reports mHz
+            refresh: f64::from(raw.refresh) / 1000.0,
+        }
+    }
+}
+
 impl absolute::Layout {
-    pub fn to_sway_commands(&self) -> impl Iterator<Item = String> + '_ {
-        self.outputs().map(|output| output.to_sway_command())
+    /// Outputs which have to be created first are preceded by `create_output`.
+    /// Note that sway chooses the name of created outputs itself,
+    /// so they might end up with a different name than in the layout.
+    ///
+    /// If the layout is [complete] and all outputs only set attributes
+    /// which they share with each other,
+    /// those are set at once using a single `output *`
+    /// instead of one command per output.
+    ///
+    /// Outputs are called like in `names` if they're in there.
+    ///
+    /// [complete]: absolute::Layout::complete
+    pub fn to_sway_commands<'a>(
+        &'a self,
+        names: &'a CustomNames,
+    ) -> impl Iterator<Item = String> + 'a {
+        let shared = self.shared();
+        let per_output = self.outputs().flat_map(move |output| {
+            let create = output.cfg.create.then(|| "create_output".to_string());
+            create
+                .into_iter()
+                .chain(output.to_sway_command_except(&shared, Version::LATEST, names))
+        });
+
+        per_output.chain(shared.to_sway_command())
+    }
+
+    /// Like [`absolute::Layout::to_sway_commands`],
+    /// but also includes what's never applied directly,
+    /// like each [background].
+    /// Each line works both as command and in a Sway config file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{comms::{offline::Offline, CustomNames}, relative};
+    /// let desc: relative::Layout = "dp @ 1080p % bg ~/wall.png fill".parse()?;
+    /// let layout = desc.to_absolute(&mut Offline::default())?.layout;
+    ///
+    /// let directives: Vec<_> = layout.to_sway_directives(&CustomNames::default()).collect();
+    /// assert_eq!(directives.last().unwrap(), r#"output DP-1 bg "~/wall.png" fill"#);
+    /// // but it's never applied directly
+    /// assert!(layout.to_sway_commands(&CustomNames::default()).all(|cmd| !cmd.contains(" bg ")));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [background]: absolute::OutputConfig::background
+    pub fn to_sway_directives<'a>(
+        &'a self,
+        names: &'a CustomNames,
+    ) -> impl Iterator<Item = String> + 'a {
+        let backgrounds = self.outputs().filter_map(|output| {
+            let bg = output.cfg.background.as_ref()?;
+            let name = names.name_of(*output.port);
+            Some(format!("output {name} {}", bg_segment(bg)))
+        });
+
+        self.to_sway_commands(names).chain(backgrounds)
+    }
+
+    /// Like [`absolute::Layout::to_sway_directives`],
+    /// but grouped into one `output <name> { ... }` block per output,
+    /// ready to be pasted into a Sway config file.
+    ///
+    /// Sway can't create outputs from its config file,
+    /// so outputs which have to be created first
+    /// only get a comment saying so above their block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{comms::{offline::Offline, CustomNames}, relative};
+    /// let desc: relative::Layout = r#"dp @ 1080p % bg "~/my wall.png" fill"#.parse()?;
+    /// let layout = desc.to_absolute(&mut Offline::default())?.layout;
+    ///
+    /// assert_eq!(
+    ///     layout.to_sway_config(&CustomNames::default()),
+    ///     r#"output "DP-1" {
+    ///     position 0 0
+    ///     resolution 1920x1080
+    ///     bg "~/my wall.png" fill
+    /// }
+    /// "#,
+    /// );
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn to_sway_config(&self, names: &CustomNames) -> String {
+        let shared = self.shared();
+        let mut config = String::new();
+
+        for output in self.outputs() {
+            let mut segments = output.sway_segments_except(&shared, Version::LATEST);
+            if let Some(bg) = &output.cfg.background {
+                segments.push(bg_segment(bg));
+            }
+            if segments.is_empty() {
+                continue;
+            }
+
+            if output.cfg.create {
+                config.push_str(
+                    "# has to be created first via `create_output`, sway names it itself\n",
+                );
+            }
+            write_block(&mut config, &quote(&names.name_of(*output.port)), &segments);
+        }
+
+        let shared = shared.segments();
+        if !shared.is_empty() {
+            write_block(&mut config, "*", &shared);
+        }
+
+        config
+    }
+
+    /// Attributes which every output selects with the same value,
+    /// if there are at least 2 outputs, the layout is [complete]
+    /// and no output needs a command of its own anyway.
+    /// Otherwise, `output *` would only add a command on top
+    /// and also affect outputs connected later on.
+    ///
+    /// [complete]: absolute::Layout::complete
+    fn shared(&self) -> Shared {
+        if !self.complete || self.outputs.len() < 2 {
+            return Shared::default();
+        }
+
+        let all: Vec<_> = self.outputs.values().map(Shared::of).collect();
+        let shared = Shared {
+            scale: common_value(&all, |own| own.scale),
+            transform: common_value(&all, |own| own.transform),
+            adaptive_sync: common_value(&all, |own| own.adaptive_sync),
+            max_render_time: common_value(&all, |own| own.max_render_time),
+            subpixel: common_value(&all, |own| own.subpixel),
+            render_bit_depth: common_value(&all, |own| own.render_bit_depth),
+            power: common_value(&all, |own| own.power),
+        };
+
+        let replaces_all = self.outputs().all(|output| output.only_sets(&shared));
+        if replaces_all {
+            shared
+        } else {
+            Shared::default()
+        }
+    }
+}
+
+/// Sets `bg` as background, with the path quoted.
+fn bg_segment(bg: &Background) -> String {
+    format!("bg {} {}", quote(&bg.path.display().to_string()), bg.mode)
+}
+
+/// `raw` in double quotes, so that Sway takes it as-is
+/// even if it contains spaces, quotes or backslashes.
+fn quote(raw: &str) -> String {
+    let escaped = raw.replace('\\', r"\\").replace('"', r#"\""#);
+    format!("\"{escaped}\"")
+}
+
+/// Appends `output <name> { ... }` with one segment per line to `config`.
+fn write_block(config: &mut String, name: &str, segments: &[String]) {
+    writeln!(config, "output {name} {{").unwrap();
+    for segment in segments {
+        writeln!(config, "    {segment}").unwrap();
+    }
+    config.push_str("}\n");
+}
+
+/// The value `field` has in all of `all`,
+/// or [`None`] if it's [`None`] or differs in at least one of them.
+fn common_value<T: Copy + PartialEq>(all: &[Shared], field: fn(&Shared) -> Option<T>) -> Option<T> {
+    let (first, rest) = all.split_first()?;
+    let value = field(first)?;
+    rest.iter()
+        .all(|other| field(other) == Some(value))
+        .then_some(value)
+}
+
+/// Attributes which can be set for all outputs at once.
+/// [`None`] if not to be sent.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Shared {
+    scale: Option<f64>,
+    transform: Option<Transform>,
+    adaptive_sync: Option<bool>,
+    max_render_time: Option<u32>,
+    subpixel: Option<Subpixel>,
+    render_bit_depth: Option<u8>,
+    power: Option<bool>,
+}
+
+impl Shared {
+    /// The ones of `cfg` which are to be sent.
+    fn of(cfg: &OutputConfig) -> Self {
+        Self {
+            scale: cfg.scale.filter(|_| cfg.explicit.scale),
+            transform: cfg.explicit.transform.then_some(cfg.transform),
+            // sway can't be told the range, just that the refresh rate may vary
+            adaptive_sync: cfg.refresh_range.map(|_| true),
+            max_render_time: cfg.max_render_time,
+            subpixel: cfg.subpixel,
+            render_bit_depth: cfg.render_bit_depth,
+            power: cfg.power,
+        }
+    }
+
+    /// Only the attributes which aren't already sent by `other`.
+    fn without(self, other: &Self) -> Self {
+        Self {
+            scale: self.scale.filter(|_| other.scale.is_none()),
+            transform: self.transform.filter(|_| other.transform.is_none()),
+            adaptive_sync: self.adaptive_sync.filter(|_| other.adaptive_sync.is_none()),
+            max_render_time: self
+                .max_render_time
+                .filter(|_| other.max_render_time.is_none()),
+            subpixel: self.subpixel.filter(|_| other.subpixel.is_none()),
+            render_bit_depth: self
+                .render_bit_depth
+                .filter(|_| other.render_bit_depth.is_none()),
+            power: self.power.filter(|_| other.power.is_none()),
+        }
+    }
+
+    /// Only the attributes `version` knows about.
+    fn supported_by(self, version: Version, target: &str) -> Self {
+        let supports = |required, attribute| version.supports(required, attribute, target);
+
+        Self {
+            adaptive_sync: self
+                .adaptive_sync
+                .filter(|_| supports(Version::ADAPTIVE_SYNC, "adaptive_sync")),
+            max_render_time: self
+                .max_render_time
+                .filter(|_| supports(Version::MAX_RENDER_TIME, "max_render_time")),
+            subpixel: self
+                .subpixel
+                .filter(|_| supports(Version::SUBPIXEL, "subpixel")),
+            render_bit_depth: self
+                .render_bit_depth
+                .filter(|_| supports(Version::RENDER_BIT_DEPTH, "render_bit_depth")),
+            power: self.power.filter(|_| supports(Version::POWER, "power")),
+            ..self
+        }
+    }
+
+    fn segments(&self) -> Vec<String> {
+        let mut segments = Vec::new();
+
+        if let Some(scale) = self.scale {
+            segments.push(format!("scale {scale}"));
+        }
+
+        if let Some(transform) = self.transform {
+            segments.push(format!("transform {}", transform.to_sway()));
+        }
+
+        if let Some(on) = self.adaptive_sync {
+            segments.push(format!("adaptive_sync {}", if on { "on" } else { "off" }));
+        }
+
+        if let Some(ms) = self.max_render_time {
+            segments.push(format!("max_render_time {ms}"));
+        }
+
+        if let Some(subpixel) = self.subpixel {
+            segments.push(format!("subpixel {subpixel}"));
+        }
+
+        if let Some(depth) = self.render_bit_depth {
+            segments.push(format!("render_bit_depth {depth}"));
+        }
+
+        if let Some(on) = self.power {
+            segments.push(format!("power {}", if on { "on" } else { "off" }));
+        }
+
+        segments
+    }
+
+    /// `output * ...` setting all of these at once,
+    /// or [`None`] if there's nothing to set.
+    fn to_sway_command(self) -> Option<String> {
+        let segments = self.segments();
+        (!segments.is_empty()).then(|| format!("output * {}", segments.join(" ")))
     }
 }
 
 impl OutputRef<'_> {
+    /// Only includes the fields selected by [`OutputConfig::explicit`]
+    /// and the ones which are [`Some`].
+    /// If that'd be nothing at all,
+    /// the output is just enabled if it's active,
+    /// otherwise there's nothing to send and [`None`] is returned.
+    /// Inactive outputs are only disabled if [`Explicit::active`] is set.
+    /// The output is called like in `names` if it's in there.
     #[must_use]
-    pub fn to_sway_command(&self) -> String {
+    pub fn to_sway_command(&self, names: &CustomNames) -> Option<String> {
+        self.to_sway_command_except(&Shared::default(), Version::LATEST, names)
+    }
+
+    /// Like [`OutputRef::to_sway_command`],
+    /// but leaves out what's already sent for all outputs via `shared`
+    /// and what `version` doesn't know yet.
+    fn to_sway_command_except(
+        self,
+        shared: &Shared,
+        version: Version,
+        names: &CustomNames,
+    ) -> Option<String> {
+        let segments = self.sway_segments_except(shared, version);
+        let name = names.name_of(*self.port);
+        (!segments.is_empty()).then(|| format!("output {name} {}", segments.join(" ")))
+    }
+
+    /// What [`OutputRef::to_sway_command_except`] would set, one attribute each.
+    /// Empty if there's nothing to send.
+    fn sway_segments_except(self, shared: &Shared, version: Version) -> Vec<String> {
         let OutputConfig {
             bounds,
             resolution,
-            scale,
+            refresh,
+            explicit,
             ..
         } = self.cfg;
 
-        let mut cmd = format!(
-            concat!(
-                "output {port} ",
-                "position {pos_x} {pos_y} ",
-                "scale {scale} ",
-                "transform {transform}",
-            ),
-            port = self.port,
-            pos_x = bounds.x.start(),
-            pos_y = bounds.y.start(),
-            scale = scale,
-            transform = self.cfg.transform.to_sway(),
-        );
-
-        if let Some(res) = resolution {
-            write!(cmd, " resolution {}x{}", res.width, res.height).unwrap();
-        }
-
-        cmd
+        let mut segments = Vec::new();
+
+        if explicit.position {
+            segments.push(format!(
+                "position {} {}",
+                bounds.x.start(),
+                bounds.y.start()
+            ));
+        }
+
+        if let Some(res) = resolution.filter(|_| explicit.resolution) {
+            let mut segment = format!("resolution {res}");
+            if let Some(refresh) = refresh {
+                write!(segment, "@{refresh}Hz").unwrap();
+            }
+            segments.push(segment);
+        }
+
+        let own = Shared::of(self.cfg)
+            .without(shared)
+            .supported_by(version, &self.port.to_string());
+        segments.extend(own.segments());
+
+        if !self.cfg.active && explicit.active {
+            segments.push("disable".to_string());
+        } else if segments.is_empty() && self.cfg.active && *shared == Shared::default() {
+            segments.push("enable".to_string());
+        }
+
+        segments
+    }
+
+    /// Whether everything to be sent for this output is already in `shared`.
+    fn only_sets(self, shared: &Shared) -> bool {
+        let cfg = self.cfg;
+        let own = Shared::of(cfg).without(shared);
+        !cfg.create
+            && !cfg.explicit.position
+            && !cfg.explicit.resolution
+            && (cfg.active || !cfg.explicit.active)
+            && own == Shared::default()
     }
 }
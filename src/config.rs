@@ -1,28 +1,554 @@
-use std::{fs, io, path::PathBuf};
+use std::{
+    cmp::Reverse,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
 
 use directories_next::ProjectDirs;
-use serde::{Deserialize, Serialize};
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
+use toml_edit::{value, Array, DocumentMut, InlineTable, Item, Table};
 
-use crate::Map;
+use crate::{
+    absolute,
+    comms::{self, Port},
+    geometry::{Pixel, Rounding, Size},
+    info::{Connector, Resolution},
+    log,
+    parse::dsl::{Names, ParseError},
+    relative::{self, Flow, Position, Target},
+    Map,
+};
 
 pub type Machine = String;
 pub type LayoutDesc = String;
+pub type ProfileName = String;
 
 /// All layouts for all machines.
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
 pub struct Config {
+    #[serde(default)]
     pub machines: Map<Machine, LayoutDesc>,
+    #[serde(default)]
+    pub profiles: Map<ProfileName, Profile>,
+    #[serde(default)]
+    pub defaults: Defaults,
+    /// Output names the WM uses that layaway doesn't know,
+    /// mapped to the port to treat them as, like in the layout description.
+    /// See [`Config::custom_names`].
+    #[serde(default)]
+    pub connectors: Map<comms::Name, String>,
+    /// Additional names for resolutions in the layout description,
+    /// mapped to the size they stand for, like `3000x2000`.
+    /// See [`Config::custom_resolutions`].
+    #[serde(default)]
+    pub resolutions: Map<String, String>,
+    /// Additional names for ports in the layout description,
+    /// mapped to the port they stand for, like `dp2`.
+    /// See [`Config::port_aliases`].
+    #[serde(default)]
+    pub aliases: Map<String, String>,
+}
+
+/// Characters with a special meaning in the keys of [`Config::machines`].
+const WILDCARDS: [char; 2] = ['*', '?'];
+
+/// If `text` matches the glob `pattern`, see [`Config::layout_for`].
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // where the last `*` is in the pattern
+    // and where in the text its match would end if it ate one more character
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t + 1));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => {
+                let Some((star, next)) = backtrack else {
+                    return false;
+                };
+                backtrack = Some((star, next + 1));
+                p = star + 1;
+                t = next;
+            }
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// If `name` starts with a letter and consists only of letters, digits and `_`,
+/// so it can't be confused with anything else in the layout description.
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A layout which is chosen automatically
+/// if the screens it mentions are connected
+/// and its conditions hold.
+/// See [`Config::best_profile`] for details.
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct Profile {
+    pub layout: LayoutDesc,
+    #[serde(default)]
+    pub when: Conditions,
+    /// The only outputs this profile may change,
+    /// named like in the layout description, e.g. `["dp2", "edp"]`.
+    /// [`None`] allows all of them.
+    pub allow: Option<Vec<String>>,
+    /// Outputs this profile never changes, even if its layout mentions them,
+    /// named like in the layout description, e.g. `["hdmi"]`.
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl Profile {
+    /// If this profile may change the output at `port`,
+    /// according to [`Self::allow`] and [`Self::deny`],
+    /// which may use the aliases in `names`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{comms::Port, config::Profile, info::Connector, parse::dsl::Names};
+    /// let profile: Profile = toml::from_str(
+    ///     r#"
+    ///     layout = "dp + hdmi + dp2"
+    ///     deny = ["hdmi", "main"]
+    ///     "#,
+    /// )?;
+    /// let dp = |idx| Port { kind: Connector::DisplayPort, idx };
+    /// let names = Names {
+    ///     aliases: [("main".to_string(), dp(2))].into(),
+    ///     ..Names::default()
+    /// };
+    /// assert!(profile.permits(dp(1), &names)?);
+    /// assert!(!profile.permits(Port { kind: Connector::HdmiA, idx: 1 }, &names)?);
+    /// assert!(!profile.permits(dp(2), &names)?);
+    /// // without knowing the alias, `main` can't be told apart from a typo
+    /// assert!(profile.permits(dp(1), &Names::default()).is_err());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn permits(&self, port: Port, names: &Names) -> Result<bool, Error> {
+        let mentions = |ports: &[String]| -> Result<bool, Error> {
+            for name in ports {
+                let parsed =
+                    Port::parse_with(name, names).map_err(|err| Error::RestrictedPort {
+                        port: name.clone(),
+                        err,
+                    })?;
+                if parsed == port {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        };
+
+        let allowed = match &self.allow {
+            Some(allow) => mentions(allow)?,
+            None => true,
+        };
+        Ok(allowed && !mentions(&self.deny)?)
+    }
+}
+
+/// What has to be true for a [`Profile`] to be chosen.
+/// Unspecified conditions always hold.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub struct Conditions {
+    /// An environment variable which has to be set,
+    /// either just `NAME` to be set at all
+    /// or `NAME=value` to be set to exactly `value`.
+    pub env: Option<String>,
+
+    /// A screen which has to be connected,
+    /// by its make, model and/or serial number like `DELL U2720Q`,
+    /// no matter on which port.
+    /// See `--info` for how the WM reports them
+    /// and [`Identity::matches`](absolute::Identity::matches) for how they're compared.
+    pub monitor: Option<String>,
+}
+
+impl Conditions {
+    /// If all conditions are true at this moment,
+    /// given the `current` layout of the WM.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{Identity, Layout, Output, OutputConfig},
+    /// #     comms::Port,
+    /// #     config::Conditions,
+    /// #     info::Connector,
+    /// # };
+    /// let identity = Identity {
+    ///     make: "Dell Inc.".to_string(),
+    ///     model: "DELL U2720Q".to_string(),
+    ///     serial: None,
+    /// };
+    /// let port = Port { kind: Connector::DisplayPort, idx: 5 };
+    /// let cfg = OutputConfig { identity: Some(identity), ..OutputConfig::default() };
+    /// let current: Layout = [Output { port, cfg }].into_iter().collect();
+    ///
+    /// let monitor = |spec: &str| Conditions { monitor: Some(spec.to_string()), env: None };
+    /// assert!(monitor("u2720q").hold(&current));
+    /// assert!(!monitor("U2723QE").hold(&current));
+    /// ```
+    #[must_use]
+    pub fn hold(&self, current: &absolute::Layout) -> bool {
+        if let Some(spec) = &self.env {
+            let set = match spec.split_once('=') {
+                Some((name, expected)) => env::var_os(name).is_some_and(|value| value == expected),
+                None => env::var_os(spec).is_some(),
+            };
+            if !set {
+                return false;
+            }
+        }
+
+        if let Some(spec) = &self.monitor {
+            let connected = current
+                .outputs
+                .values()
+                .filter_map(|cfg| cfg.identity.as_ref())
+                .any(|identity| identity.matches(spec));
+            if !connected {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Settings used if the layout description doesn't specify otherwise.
+///
+/// Some of them correspond to CLI flags,
+/// which take precedence if they're given.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{config::Config, geometry::Rounding};
+/// let config: Config = toml::from_str(
+///     r#"
+///     [defaults]
+///     rounding = "floor"
+///     emit_all = true
+///     "#,
+/// )?;
+/// assert_eq!(config.defaults.rounding, Some(Rounding::Floor));
+/// assert!(config.defaults.emit_all);
+/// assert_eq!(config.defaults.wrap_width, None);
+/// # Ok::<_, toml::de::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub struct Defaults {
+    /// Where to place screens which don't specify a position,
+    /// in the same syntax as `pos` in the DSL, e.g. `bottom,center`.
+    pub position: Option<Position>,
+
+    /// In which direction screens without position are added,
+    /// either `horizontal` or `vertical`.
+    /// Ignored if `position` is specified.
+    pub flow: Option<Flow>,
+
+    /// Place the internal screen of a laptop first
+    /// and the first external one without position above it,
+    /// like `--around-internal`.
+    #[serde(default)]
+    pub around_internal: bool,
+
+    /// How to round the size of scaled screens to whole pixels,
+    /// like `--rounding`.
+    pub rounding: Option<Rounding>,
+
+    /// Send all attributes of the calculated layout to the WM,
+    /// like `--emit-all`.
+    #[serde(default)]
+    pub emit_all: bool,
+
+    /// How far away from 0,0 screens may reach at most, in pixels,
+    /// like `--max-coordinate`.
+    pub max_coordinate: Option<Pixel>,
+
+    /// Start a new row once the layout would become wider than this, in pixels,
+    /// like `--wrap-width`.
+    pub wrap_width: Option<Pixel>,
+
+    /// Refuse to apply layouts with screens that aren't connected,
+    /// like `--strict`.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl Defaults {
+    /// Where to place screens which don't specify a position.
+    #[must_use]
+    pub fn position(&self) -> Position {
+        self.position
+            .or_else(|| self.flow.map(Flow::default_pos))
+            .unwrap_or_default()
+    }
 }
 
 impl Config {
+    /// [`Self::connectors`] with the ports parsed,
+    /// ready for [`comms::establish`].
+    pub fn custom_names(&self) -> Result<comms::CustomNames, Error> {
+        let names = self
+            .connectors
+            .iter()
+            .map(|(name, port)| {
+                let parsed = port.parse().map_err(|err| Error::CustomPort {
+                    name: name.clone(),
+                    port: port.clone(),
+                    err,
+                })?;
+                Ok((name.clone(), parsed))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(comms::CustomNames(names))
+    }
+
+    /// [`Self::resolutions`] with the sizes parsed,
+    /// ready for [`Names::resolutions`].
+    ///
+    /// Names have to start with a letter and consist of only letters, digits and `_`,
+    /// so they can't be confused with sizes.
+    /// They also can't shadow built-in ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{config::{Config, Error}, geometry::Size};
+    /// let config: Config = toml::from_str(r#"
+    ///     [resolutions]
+    ///     myscreen = "3000x2000"
+    /// "#)?;
+    /// let size = Size { width: 3000, height: 2000 };
+    /// assert_eq!(config.custom_resolutions()?["myscreen"], size);
+    ///
+    /// let config: Config = toml::from_str(r#"
+    ///     [resolutions]
+    ///     dci = "4096x2160"
+    /// "#)?;
+    /// assert!(matches!(
+    ///     config.custom_resolutions(),
+    ///     Err(Error::ShadowedResolution { builtin: "dci2k", .. }),
+    /// ));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn custom_resolutions(&self) -> Result<Map<String, Size>, Error> {
+        self.resolutions
+            .iter()
+            .map(|(name, size)| {
+                if !is_valid_name(name) {
+                    return Err(Error::ResolutionName { name: name.clone() });
+                }
+                if let Some(builtin) = Resolution::shadowed_by(name) {
+                    return Err(Error::ShadowedResolution {
+                        name: name.clone(),
+                        builtin,
+                    });
+                }
+
+                let parsed = size.parse().map_err(|err| Error::CustomResolution {
+                    name: name.clone(),
+                    size: size.clone(),
+                    err,
+                })?;
+                Ok((name.clone(), parsed))
+            })
+            .collect()
+    }
+
+    /// [`Self::aliases`] with the ports parsed,
+    /// ready for [`Names::aliases`].
+    ///
+    /// Names follow the same rules as in [`Self::custom_resolutions`],
+    /// just that they can't shadow built-in connectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{comms::Port, config::{Config, Error}, info::Connector};
+    /// let config: Config = toml::from_str(r#"
+    ///     [aliases]
+    ///     main = "dp2"
+    /// "#)?;
+    /// let dp2 = Port { kind: Connector::DisplayPort, idx: 2 };
+    /// assert_eq!(config.port_aliases()?["main"], dp2);
+    ///
+    /// let config: Config = toml::from_str(r#"
+    ///     [aliases]
+    ///     hd = "dp2"
+    /// "#)?;
+    /// assert!(matches!(
+    ///     config.port_aliases(),
+    ///     Err(Error::ShadowedConnector { builtin: "hdmib", .. }),
+    /// ));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn port_aliases(&self) -> Result<Map<String, Port>, Error> {
+        self.aliases
+            .iter()
+            .map(|(name, port)| {
+                if !is_valid_name(name) {
+                    return Err(Error::AliasName { name: name.clone() });
+                }
+                if let Some(builtin) = Connector::shadowed_by(name) {
+                    return Err(Error::ShadowedConnector {
+                        name: name.clone(),
+                        builtin,
+                    });
+                }
+
+                let parsed = port.parse().map_err(|err| Error::AliasPort {
+                    name: name.clone(),
+                    port: port.clone(),
+                    err,
+                })?;
+                Ok((name.clone(), parsed))
+            })
+            .collect()
+    }
+
+    /// Everything this config adds to layout descriptions,
+    /// ready for [`relative::Layout::parse_with`].
+    pub fn names(&self) -> Result<Names, Error> {
+        Ok(Names {
+            resolutions: self.custom_resolutions()?,
+            aliases: self.port_aliases()?,
+        })
+    }
+
     /// Loads the current user config from disk.
     pub fn new() -> Result<Self, Error> {
+        let path = Self::default_path()?;
+        let source = fs::read_to_string(&path).map_err(|err| Error::Load { err, path })?;
+
+        let config = toml::from_str(&source)?;
+
+        Ok(config)
+    }
+
+    /// Where the user config is usually stored.
+    pub fn default_path() -> Result<PathBuf, Error> {
         let proj_dirs =
             ProjectDirs::from("org", "MultisampledNight", "layaway").ok_or(Error::UnknownHome)?;
+        Ok(proj_dirs.config_dir().join("config.toml"))
+    }
 
-        let path = proj_dirs.config_dir().join("config.toml");
-        let source = fs::read_to_string(&path).map_err(|err| Error::Load { err, path })?;
+    /// Adds the profile under the given name to the config file at `path`,
+    /// replacing the one with the same name if any.
+    /// If the file doesn't exist yet, it's created.
+    ///
+    /// Everything else in the file is left exactly as-is,
+    /// including comments and formatting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::config::{Config, Profile};
+    /// let path = std::env::temp_dir().join(format!("layaway-{}-save.toml", std::process::id()));
+    /// let profile: Profile = toml::from_str(r#"
+    ///     layout = "dp + edp/bottom"
+    ///     allow = ["dp"]
+    ///     deny = ["edp"]
+    /// "#)?;
+    /// Config::save_profile(&path, "docked", &profile)?;
+    ///
+    /// let saved = &Config::from_path(&path)?.profiles["docked"];
+    /// assert_eq!(saved.allow, profile.allow);
+    /// assert_eq!(saved.deny, profile.deny);
+    ///
+    /// // comments and other sections stay exactly as they were
+    /// let before = "# my screens\n[machines]\nlaptop = \"edp\"  # just the panel\n";
+    /// std::fs::write(&path, before)?;
+    /// Config::save_profile(&path, "docked", &profile)?;
+    /// let after = std::fs::read_to_string(&path)?;
+    /// assert!(after.starts_with(before));
+    /// assert!(after[before.len()..].contains("[profiles.docked]"));
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn save_profile(path: &Path, name: &str, profile: &Profile) -> Result<(), Error> {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => {
+                return Err(Error::LoadExplicit {
+                    err,
+                    path: path.to_path_buf(),
+                })
+            }
+        };
+        let mut doc: DocumentMut = source.parse()?;
+
+        let profiles = doc
+            .entry("profiles")
+            .or_insert_with(|| {
+                // so that only the profiles themselves get a header
+                let mut profiles = Table::new();
+                profiles.set_implicit(true);
+                Item::Table(profiles)
+            })
+            .as_table_mut()
+            .ok_or(Error::ProfilesNotATable)?;
+
+        let mut entry = Table::new();
+        entry["layout"] = value(&profile.layout);
+        let mut when = InlineTable::new();
+        if let Some(env) = &profile.when.env {
+            when.insert("env", env.into());
+        }
+        if let Some(monitor) = &profile.when.monitor {
+            when.insert("monitor", monitor.into());
+        }
+        if !when.is_empty() {
+            entry["when"] = value(when);
+        }
+        if let Some(allow) = &profile.allow {
+            entry["allow"] = value(allow.iter().collect::<Array>());
+        }
+        if !profile.deny.is_empty() {
+            entry["deny"] = value(profile.deny.iter().collect::<Array>());
+        }
+        profiles[name] = Item::Table(entry);
+
+        let save = |err| Error::Save {
+            err,
+            path: path.to_path_buf(),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(save)?;
+        }
+        fs::write(path, doc.to_string()).map_err(save)?;
+
+        Ok(())
+    }
+
+    /// Loads the config file at the given path from disk.
+    /// Unlike [`Config::new`], the file is expected to exist.
+    pub fn from_path(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let source =
+            fs::read_to_string(&path).map_err(|err| Error::LoadExplicit { err, path })?;
 
         let config = toml::from_str(&source)?;
 
@@ -42,18 +568,167 @@ impl Config {
         let Some(hostname) = hostname.to_str() else {
             return Ok(None);
         };
-        Ok(self.machines.get(hostname))
+        Ok(self.layout_for(hostname))
+    }
+
+    /// The layout description in [`Self::machines`] for the machine called `hostname`.
+    ///
+    /// Keys may contain `*` to match any number of characters
+    /// and `?` to match exactly one character, like `worker-*`.
+    /// A key that is exactly `hostname` always takes precedence.
+    /// Otherwise, of all matching patterns, the one with the most other characters wins,
+    /// since it's the most specific one.
+    /// On a tie, the alphabetically first one wins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::config::Config;
+    /// let config: Config = toml::from_str(r#"
+    ///     [machines]
+    ///     "worker-*" = "dp + edp"
+    ///     "worker-1?" = "hdmi + edp"
+    ///     "worker-13" = "edp"
+    /// "#)?;
+    ///
+    /// assert_eq!(config.layout_for("worker-02").unwrap(), "dp + edp");
+    /// assert_eq!(config.layout_for("worker-12").unwrap(), "hdmi + edp");
+    /// assert_eq!(config.layout_for("worker-13").unwrap(), "edp");
+    /// assert!(config.layout_for("laptop").is_none());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn layout_for(&self, hostname: &str) -> Option<&LayoutDesc> {
+        if let Some(desc) = self.machines.get(hostname) {
+            return Some(desc);
+        }
+
+        let literal_len =
+            |pattern: &str| pattern.chars().filter(|c| !WILDCARDS.contains(c)).count();
+        self.machines
+            .iter()
+            .filter(|(pattern, _)| pattern.contains(WILDCARDS) && glob_matches(pattern, hostname))
+            // max_by_key would pick the last one on a tie
+            .min_by_key(|(pattern, _)| Reverse(literal_len(pattern)))
+            .map(|(_, desc)| desc)
+    }
+
+    /// The profile which fits the `current` layout best, if any.
+    ///
+    /// Profiles are narrowed down in this order:
+    ///
+    /// 1. Profiles whose conditions don't hold are skipped.
+    /// 2. Profiles whose layout mentions a port that isn't connected are skipped,
+    ///    unless that port is marked as optional.
+    /// 3. Of the rest, the one mentioning the most connected ports wins,
+    ///    since it's the most specific one.
+    ///    On a tie, the alphabetically first one wins.
+    ///
+    /// Profiles whose layout can't be parsed with `names` are skipped with a warning.
+    #[must_use]
+    pub fn best_profile(
+        &self,
+        current: &absolute::Layout,
+        names: &Names,
+    ) -> Option<(&ProfileName, &Profile)> {
+        self.profiles
+            .iter()
+            .filter(|(_, profile)| profile.when.hold(current))
+            .filter_map(|(name, profile)| {
+                let layout = match relative::Layout::parse_with(&profile.layout, names) {
+                    Ok(layout) => layout,
+                    Err(err) => {
+                        log::warning!("Skipping profile `{name}` since it can't be parsed: {err}");
+                        return None;
+                    }
+                };
+
+                // `*` stands for whatever is connected, so it always fits
+                let connected =
+                    |screen: &&relative::Screen| current.outputs.contains_key(&screen.port());
+                let mentioned = || {
+                    layout
+                        .screens()
+                        .filter(|screen| screen.target != Target::Others)
+                };
+                let all_connected = mentioned().all(|screen| screen.optional || connected(&screen));
+                all_connected.then(|| (mentioned().filter(connected).count(), name, profile))
+            })
+            // max_by_key returns the last maximum, but the first one is wanted
+            .rev()
+            .max_by_key(|(mentioned, _, _)| *mentioned)
+            .map(|(_, name, profile)| (name, profile))
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        source.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Just a string as far as JSON is concerned, see the impls above.
+impl JsonSchema for Position {
+    fn schema_name() -> String {
+        "Position".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
     }
 }
 
 #[derive(Debug, Error)]
 pub enum Error {
+    #[error("Could not parse `{port}` which `{name}` is mapped to in `connectors`: {err}")]
+    CustomPort {
+        name: comms::Name,
+        port: String,
+        err: ParseError,
+    },
+    #[error("Could not parse `{size}` which `{name}` is mapped to in `resolutions`: {err}")]
+    CustomResolution {
+        name: String,
+        size: String,
+        err: ParseError,
+    },
+    #[error("Resolution name `{name}` in `resolutions` has to start with a letter and consist only of letters, digits and `_`")]
+    ResolutionName { name: String },
+    #[error("Resolution name `{name}` in `resolutions` would shadow the built-in `{builtin}`")]
+    ShadowedResolution { name: String, builtin: &'static str },
+    #[error("Could not parse `{port}` which `{name}` is mapped to in `aliases`: {err}")]
+    AliasPort {
+        name: String,
+        port: String,
+        err: ParseError,
+    },
+    #[error("Alias `{name}` in `aliases` has to start with a letter and consist only of letters, digits and `_`")]
+    AliasName { name: String },
+    #[error("Alias `{name}` in `aliases` would shadow the built-in connector `{builtin}`")]
+    ShadowedConnector { name: String, builtin: &'static str },
+    #[error("Could not parse `{port}` in `allow` or `deny` of a profile: {err}")]
+    RestrictedPort { port: String, err: ParseError },
     #[error("directories-next could not determine the home directory")]
     UnknownHome,
     #[error(
         "Could not load config file at `{path}` from disk, maybe it doesn't exist yet?\n{err}"
     )]
     Load { err: io::Error, path: PathBuf },
+    #[error("Could not load explicitly given config file at `{path}` from disk: {err}")]
+    LoadExplicit { err: io::Error, path: PathBuf },
     #[error("Could not parse config file: {0}")]
     Toml(#[from] toml::de::Error),
+    #[error("Could not parse config file for editing: {0}")]
+    TomlEdit(#[from] toml_edit::TomlError),
+    #[error("`profiles` in the config file is not a table")]
+    ProfilesNotATable,
+    #[error("Could not save config file at `{path}`: {err}")]
+    Save { err: io::Error, path: PathBuf },
 }
@@ -0,0 +1,204 @@
+//! Lets an already running instance apply layout descriptions sent to it
+//! over a Unix socket, e.g. from keybindings,
+//! without having to start up each time.
+//!
+//! Each connection sends exactly one layout description
+//! and then closes its writing half.
+//! The reply is either `ok` on the first line followed by one warning per line,
+//! or `error` on the first line followed by what went wrong,
+//! then the connection is closed.
+
+use std::{
+    env, fmt, fs,
+    io::{self, Read, Write},
+    net::Shutdown,
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use thiserror::Error;
+
+use crate::{
+    absolute,
+    comms::Comms,
+    convert::{self, Context, Warning},
+    log,
+    parse::dsl::Names,
+    relative,
+};
+
+/// First line of the reply if the layout was applied.
+const OK: &str = "ok";
+/// First line of the reply if the layout was not applied.
+const ERROR: &str = "error";
+/// How long a client may take to send its layout description,
+/// so one that never finishes doesn't block all others.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("`XDG_RUNTIME_DIR` is not set, so there's no default place for the control socket")]
+    NoRuntimeDir,
+    #[error("Could not listen on control socket at `{}`: {err}", .path.display())]
+    Listen { err: io::Error, path: PathBuf },
+    #[error("Could not reach control socket at `{}`, is `layaway --listen` running? {err}", .path.display())]
+    Connect { err: io::Error, path: PathBuf },
+    #[error("Could not talk over control socket: {0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Rejected(String),
+}
+
+/// `$XDG_RUNTIME_DIR/layaway.sock`,
+/// so it's only reachable by the user running layaway.
+pub fn default_path() -> Result<PathBuf, Error> {
+    let dir = env::var_os("XDG_RUNTIME_DIR").ok_or(Error::NoRuntimeDir)?;
+    Ok(PathBuf::from(dir).join("layaway.sock"))
+}
+
+/// Converts each layout description sent to the socket at `path` with `opts`,
+/// knowing about `names`,
+/// and hands the result to `emit`, e.g. to apply it via `comms`.
+/// What the WM knows about its outputs is kept in between,
+/// see [`Context::refresh`].
+///
+/// Only returns if the socket can't be listened on.
+/// A left-over socket of an instance that isn't running anymore is replaced.
+pub fn serve<E: fmt::Display>(
+    path: &Path,
+    comms: &mut dyn Comms,
+    opts: &convert::Options,
+    names: &Names,
+    emit: &mut dyn FnMut(absolute::Layout, &mut dyn Comms) -> Result<(), E>,
+) -> Result<(), Error> {
+    let listen_err = |err| Error::Listen {
+        err,
+        path: path.to_path_buf(),
+    };
+
+    // nobody's answering, so it's just left over
+    if path.exists() && UnixStream::connect(path).is_err() {
+        fs::remove_file(path).map_err(listen_err)?;
+    }
+    let listener = UnixListener::bind(path).map_err(listen_err)?;
+    log::verbose!("Listening for layout descriptions on `{}`", path.display());
+
+    let mut ctx = Context::default();
+    for stream in listener.incoming() {
+        // one broken client shouldn't stop all the others
+        let handled = stream.and_then(|stream| handle(stream, &mut ctx, comms, opts, names, emit));
+        if let Err(err) = handled {
+            log::warning!("Could not handle client of control socket: {err}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads one layout description from `stream`, converts and emits it
+/// and replies how it went.
+fn handle<E: fmt::Display>(
+    mut stream: UnixStream,
+    ctx: &mut Context,
+    comms: &mut dyn Comms,
+    opts: &convert::Options,
+    names: &Names,
+    emit: &mut dyn FnMut(absolute::Layout, &mut dyn Comms) -> Result<(), E>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut desc = String::new();
+    stream.read_to_string(&mut desc)?;
+    let desc = desc.trim();
+    log::verbose!("Received `{desc}` on control socket");
+
+    let reply = match plan(desc, names, ctx, comms, opts) {
+        Ok(convert::Converted {
+            layout, warnings, ..
+        }) => match emit(layout, comms) {
+            Ok(()) => accepted(&warnings),
+            Err(err) => rejected(desc, &err),
+        },
+        Err(err) => rejected(desc, &err),
+    };
+    stream.write_all(reply.as_bytes())
+}
+
+/// Converts `desc` through `ctx`,
+/// after bringing it up to date with what changed in the WM since the last one.
+fn plan(
+    desc: &str,
+    names: &Names,
+    ctx: &mut Context,
+    comms: &mut dyn Comms,
+    opts: &convert::Options,
+) -> Result<convert::Converted, crate::Error> {
+    let relative = relative::Layout::parse_with(desc, names)?;
+    ctx.refresh(comms)?;
+    Ok(ctx.convert(&relative, opts)?)
+}
+
+/// Reply for a layout description that went through, with its `warnings`.
+fn accepted(warnings: &[Warning]) -> String {
+    let warnings = warnings.iter().map(|warning| format!("{warning}\n"));
+    format!("{OK}\n{}", warnings.collect::<String>())
+}
+
+/// Reply for a layout description that couldn't be applied due to `err`.
+fn rejected(desc: &str, err: &dyn fmt::Display) -> String {
+    log::warning!("Could not apply `{desc}` received on control socket: {err:#}");
+    format!("{ERROR}\n{err:#}\n")
+}
+
+/// Sends `desc` to the instance listening at `path`
+/// and waits until it's applied.
+///
+/// Returns the warnings of the conversion, one per line,
+/// or [`Error::Rejected`] with what went wrong.
+///
+/// # Examples
+///
+/// ```
+/// # use std::{os::unix::net::UnixStream, thread};
+/// # use layaway::{comms::offline::Offline, control, convert::Options, parse::dsl::Names};
+/// let path = std::env::temp_dir().join(format!("layaway-{}.sock", std::process::id()));
+/// let listening = path.clone();
+/// thread::spawn(move || {
+///     let names = Names::default();
+///     let opts = Options::default();
+///     control::serve(&listening, &mut Offline::default(), &opts, &names, &mut |layout, _| {
+///         // nothing to apply to, so just show it
+///         println!("{layout:?}");
+///         Ok::<_, std::convert::Infallible>(())
+///     })
+/// });
+/// # while UnixStream::connect(&path).is_err() {
+/// #     thread::yield_now();
+/// # }
+///
+/// assert!(control::send(&path, "headless@1080p").is_ok());
+/// assert!(matches!(
+///     control::send(&path, "dp + edp/sideways"),
+///     Err(control::Error::Rejected(_)),
+/// ));
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn send(path: &Path, desc: &str) -> Result<String, Error> {
+    let mut stream = UnixStream::connect(path).map_err(|err| Error::Connect {
+        err,
+        path: path.to_path_buf(),
+    })?;
+    stream.write_all(desc.as_bytes())?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+
+    let (status, rest) = reply.split_once('\n').unwrap_or((&reply, ""));
+    if status == OK {
+        Ok(rest.to_string())
+    } else {
+        Err(Error::Rejected(rest.trim_end().to_string()))
+    }
+}
@@ -1,85 +1,1602 @@
 //! Concretizes [`relative::Layout`] into [`absolute::Layout`]
 
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
+
+use thiserror::Error;
+
 use crate::{
-    absolute,
-    comms::{self, Comms},
-    geometry::Rect,
-    relative::{self, Position},
+    absolute::{self, AuditKind},
+    comms::{self, Comms, Port},
+    geometry::{
+        Hori, HoriSpec, Interval, MaybeCenter, PhysicalSize, Pixel, Point, Rect, Rotation,
+        Rounding, Size, Vert, VertSpec,
+    },
+    info::Connector,
+    log,
+    relative::{self, Flow, Position, Screen, Target},
+    Map,
 };
 
+/// Knobs for how [`relative::Layout::to_absolute_with`] behaves.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Where to place screens which don't specify a position themselves.
+    pub default_pos: Position,
+    /// How to round the size of scaled screens to whole pixels.
+    ///
+    /// Each screen's size is divided by its scale and rounded exactly once.
+    /// Positions are only ever derived from the already rounded sizes,
+    /// so the WM sees the same whole-pixel sizes layaway placed
+    /// and no rounding error adds up along chains of screens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{comms::offline::Offline, relative};
+    /// let relative: relative::Layout = "{dp1 + dp2 + dp3 + dp4 + dp5}@2560x1440:1.5".parse()?;
+    /// let layout = relative.to_absolute(&mut Offline::default())?.layout;
+    ///
+    /// // 2560 / 1.5 = 1706.66..., rounded to the nearest pixel
+    /// let xs: Vec<_> = layout.outputs().map(|output| output.cfg.bounds.x).collect();
+    /// assert!(xs.iter().all(|x| x.len() == 1707));
+    /// // no gaps and no overlaps, even after five screens
+    /// assert!(xs.windows(2).all(|pair| pair[0].end() == pair[1].start()));
+    /// assert_eq!(xs[4].end(), 5 * 1707);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub rounding: Rounding,
+    /// Scale to use for all screens,
+    /// even if they specify one themselves.
+    /// [`None`] to use the scale of each screen.
+    pub all_scale: Option<f64>,
+    /// Screen to keep at 0,0, placing all others around it.
+    /// [`None`] to move the whole layout into the positive space instead.
+    pub origin: Option<Port>,
+    /// How far away from 0,0 screens may reach at most, in both directions.
+    /// Some applications and WMs break on larger coordinates.
+    pub max_coordinate: Pixel,
+    /// Place the internal screen of each group first,
+    /// wherever it's listed,
+    /// and the first screen after it without a position above it
+    /// instead of at [`Self::default_pos`].
+    /// See [`Connector::is_internal`] for what counts as internal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     comms::{offline::Offline, Port},
+    /// #     convert::Options,
+    /// #     info::Connector,
+    /// #     relative,
+    /// # };
+    /// let relative: relative::Layout = "dp@1080p + hdmi@1080p + edp@1080p".parse()?;
+    /// let opts = Options { around_internal: true, ..Options::default() };
+    /// let layout = relative.to_absolute_with(&mut Offline::default(), &opts)?.layout;
+    ///
+    /// let corner = |kind| {
+    ///     let bounds = layout.outputs[&Port { kind, idx: 1 }].bounds;
+    ///     (bounds.x.start(), bounds.y.start())
+    /// };
+    /// // the external ones are next to each other, above the internal one
+    /// assert_eq!(corner(Connector::DisplayPort), (0, 0));
+    /// assert_eq!(corner(Connector::HdmiA), (1920, 0));
+    /// assert_eq!(corner(Connector::Edp), (0, 1080));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub around_internal: bool,
+    /// How wide the layout may become before
+    /// screens without position of their own continue in a new row,
+    /// like text wrapping.
+    /// [`None`] to never wrap.
+    ///
+    /// Screens with a position, anchor or exact position never wrap,
+    /// but start a new row themselves,
+    /// so the screens after them continue next to them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     comms::{offline::Offline, Port},
+    /// #     convert::Options,
+    /// #     info::Connector,
+    /// #     relative,
+    /// # };
+    /// let relative: relative::Layout = "dp@1080p + hdmi@1080p + vga@1080p + edp@1080p".parse()?;
+    /// let opts = Options { wrap_width: Some(4000), ..Options::default() };
+    /// let layout = relative.to_absolute_with(&mut Offline::default(), &opts)?.layout;
+    ///
+    /// let corner = |kind| {
+    ///     let bounds = layout.outputs[&Port { kind, idx: 1 }].bounds;
+    ///     (bounds.x.start(), bounds.y.start())
+    /// };
+    /// assert_eq!(corner(Connector::DisplayPort), (0, 0));
+    /// assert_eq!(corner(Connector::HdmiA), (1920, 0));
+    /// // a third one would be too wide, so it starts a new row
+    /// assert_eq!(corner(Connector::Vga), (0, 1080));
+    /// assert_eq!(corner(Connector::Edp), (1920, 1080));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub wrap_width: Option<Pixel>,
+    /// Line up each row of screens at the top, center or bottom
+    /// of its tallest screen after placing all of them,
+    /// see [`absolute::Layout::align_row`].
+    /// [`None`] to leave them where their position put them.
+    ///
+    /// Only screens directly next to each other are affected,
+    /// screens in different rows aren't aligned to each other.
+    pub align_rows: Option<VertSpec>,
+    /// Fail if any screen not marked as [optional] isn't connected,
+    /// instead of skipping it with a warning.
+    ///
+    /// [optional]: relative::Screen::optional
+    pub strict: bool,
+    /// Move the whole layout so it starts at 0,0 after placing all screens.
+    /// If `false`, screens stay where they were placed,
+    /// with the first one at 0,0 and the others possibly at negative coordinates.
+    ///
+    /// [`Self::origin`] moves the layout regardless.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     comms::{offline::Offline, Port},
+    /// #     convert::Options,
+    /// #     info::Connector,
+    /// #     relative,
+    /// # };
+    /// let relative: relative::Layout = "dp@1080p + edp@1080p/left".parse()?;
+    /// let x = |opts| -> Result<_, Box<dyn std::error::Error>> {
+    ///     let layout = relative.to_absolute_with(&mut Offline::default(), &opts)?.layout;
+    ///     Ok(layout.outputs[&Port { kind: Connector::Edp, idx: 1 }].bounds.x.start())
+    /// };
+    ///
+    /// assert_eq!(x(Options::default())?, 0);
+    /// assert_eq!(x(Options { reset_to_origin: false, ..Options::default() })?, -1920);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// Layouts with an exact position via [`relative::Screen::at`] are never moved,
+    /// so that it's where it says.
+    pub reset_to_origin: bool,
+    /// Keep all screens within this region after placing them,
+    /// e.g. the largest virtual screen a VM supports,
+    /// see [`absolute::Layout::clamp_into`].
+    /// Screens larger than the region are left out with a warning.
+    /// [`None`] to leave them where they were placed.
+    pub region: Option<Rect>,
+}
+
+impl Options {
+    /// Comfortably fits into 16 bits, which some applications seem to use.
+    pub const DEFAULT_MAX_COORDINATE: Pixel = 32767;
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            default_pos: Position::default(),
+            rounding: Rounding::default(),
+            all_scale: None,
+            origin: None,
+            max_coordinate: Self::DEFAULT_MAX_COORDINATE,
+            around_internal: false,
+            wrap_width: None,
+            align_rows: None,
+            strict: false,
+            reset_to_origin: true,
+            region: None,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Could not fetch current layout from WM: {0}")]
+    Comms(#[from] comms::Error),
+    #[error("{port} is connected, but the WM knows no mode it could be driven at, consider specifying a resolution")]
+    NoMode { port: Port },
+    #[error("{port} can't be driven at {requested}, closest available modes are {}", list(.closest))]
+    UnsupportedMode {
+        port: Port,
+        requested: String,
+        closest: Vec<absolute::Mode>,
+    },
+    #[error("{port} is placed next to {anchor}, but {anchor} isn't mentioned or placed before it in the same group")]
+    UnknownAnchor { port: Port, anchor: Port },
+    #[error("{port} should have the same scale as {like}, but the scale of {like} is unknown")]
+    UnknownScale { port: Port, like: Port },
+    #[error("Required by the layout description, but not connected: {}\nMark them with `?` if they're optional", list(.ports))]
+    Missing { ports: Vec<Port> },
+    #[error("{port} would span from {},{} to {},{}, beyond the maximum coordinate of {max}", .bounds.x.start(), .bounds.y.start(), .bounds.x.end(), .bounds.y.end())]
+    OutOfBounds {
+        port: Port,
+        bounds: Rect,
+        max: Pixel,
+    },
+    /// The screen would be less than a pixel or unimaginably large in the layout,
+    /// e.g. since a tiny custom resolution is combined with a huge scale.
+    ///
+    /// ```
+    /// # use layaway::{comms::offline::Offline, convert::Error, relative};
+    /// let relative: relative::Layout = "headless@2x2:1000".parse()?;
+    /// assert!(matches!(
+    ///     relative.to_absolute(&mut Offline::default()),
+    ///     Err(Error::DegenerateSize { .. }),
+    /// ));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[error("{port} would be {resolution} divided by {scale} large in the layout, which is no usable size, consider a different scale")]
+    DegenerateSize {
+        port: Port,
+        resolution: Size,
+        scale: f64,
+    },
+    /// A physical offset was given,
+    /// but the WM doesn't tell how large the screen is physically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{self, Layout, Output, OutputConfig},
+    /// #     comms::{self, Comms, Port},
+    /// #     convert::Error,
+    /// #     geometry::{PhysicalSize, Size},
+    /// #     info::Connector,
+    /// #     relative,
+    /// # };
+    /// struct Projector(Option<PhysicalSize>);
+    /// impl Comms for Projector {
+    ///     fn layout(&mut self) -> comms::Result<Layout> {
+    ///         let port = Port { kind: Connector::HdmiA, idx: 1 };
+    ///         let cfg = OutputConfig {
+    ///             resolution: Some(Size { width: 1920, height: 1080 }),
+    ///             physical_size: self.0,
+    ///             active: true,
+    ///             ..OutputConfig::default()
+    ///         };
+    ///         Ok([Output { port, cfg }].into_iter().collect())
+    ///     }
+    ///     # fn set_layout(&mut self, _: &Layout) -> comms::Result<()> {
+    ///     #     Ok(())
+    ///     # }
+    /// }
+    ///
+    /// // 1920 pixels on 960 mm, so 2 pixels per mm
+    /// let relative: relative::Layout = "dp@1080p + hdmi/right +5cm,0".parse()?;
+    /// let size = PhysicalSize { width: 960, height: 540 };
+    /// let layout = relative.to_absolute(&mut Projector(Some(size)))?.layout;
+    /// let hdmi = &layout.outputs[&Port { kind: Connector::HdmiA, idx: 1 }];
+    /// assert_eq!(hdmi.bounds.x.start(), 1920 + 100);
+    ///
+    /// assert!(matches!(
+    ///     relative.to_absolute(&mut Projector(None)),
+    ///     Err(Error::UnknownPhysicalSize { .. }),
+    /// ));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[error("{port} should be moved by a physical length, but the WM doesn't tell how large it is physically, consider moving it by pixels instead")]
+    UnknownPhysicalSize { port: Port },
+}
+
+/// The outcome of a successful conversion.
+#[derive(Debug)]
+pub struct Converted {
+    pub layout: absolute::Layout,
+    /// Things that are probably not intended,
+    /// but don't stop the layout from being applied.
+    pub warnings: Vec<Warning>,
+    /// The bounding box of all screens placed so far in their group
+    /// after placing each screen, in the order they were placed,
+    /// for debugging where screens end up.
+    /// Relative to the group, so before it is moved next to the others.
+    pub bounding_boxes: Vec<(Port, Rect)>,
+}
+
+/// Everything [`Context::validate`] found.
+#[derive(Debug, Default)]
+pub struct Validation {
+    /// Problems which stop the layout from being applied.
+    pub errors: Vec<Error>,
+    /// Like [`Converted::warnings`].
+    /// Empty if converting itself failed.
+    pub warnings: Vec<Warning>,
+}
+
+/// Something about a converted layout
+/// that the user should probably know about.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Warning {
+    /// The screen is in the layout description,
+    /// but isn't connected and hence was skipped.
+    Disconnected { port: Port },
+    /// The screen should be kept at 0,0,
+    /// but isn't in the layout.
+    OriginMissing { port: Port },
+    /// Something [`absolute::Layout::audit`] found about the converted layout.
+    ///
+    /// Only includes findings not already covered by the other warnings or by errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{self, AuditFinding, AuditKind},
+    /// #     comms::offline::Offline,
+    /// #     convert::Warning,
+    /// #     relative,
+    /// # };
+    /// let relative: relative::Layout = "dp@1080p + edp@1080p/below dp,left +1920,0".parse()?;
+    /// let warnings = relative.to_absolute(&mut Offline::default())?.warnings;
+    /// assert!(warnings.iter().any(|warning| matches!(
+    ///     warning,
+    ///     Warning::Audit(AuditFinding { kind: AuditKind::CornerOnly, .. }),
+    /// )));
+    ///
+    /// let relative: relative::Layout = "dp@1080p + edp@1080p/below dp,left +1900,0".parse()?;
+    /// assert!(relative.to_absolute(&mut Offline::default())?.warnings.is_empty());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    Audit(absolute::AuditFinding),
+    /// The outputs are next to each other,
+    /// but their effective DPIs differ a lot,
+    /// so windows would noticeably jump in size when moved between them.
+    DpiMismatch {
+        a: Port,
+        b: Port,
+        dpi_a: f64,
+        dpi_b: f64,
+    },
+    /// The resolution given in the layout description is larger
+    /// than [`PLAUSIBLE_RESOLUTION`] in some direction,
+    /// which is likely a typo.
+    ImplausibleResolution { port: Port, size: Size },
+    /// The resolution isn't divisible by the scale,
+    /// so the logical size has to be rounded,
+    /// which may leave gaps between screens or let them overlap.
+    /// `suggestion` is the closest scale which divides the resolution evenly, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     comms::offline::Offline,
+    /// #     convert::Warning,
+    /// #     relative,
+    /// # };
+    /// let relative: relative::Layout = "edp@1366x768:1.5".parse()?;
+    /// let warnings = relative.to_absolute(&mut Offline::default())?.warnings;
+    /// let Some(Warning::FractionalLogicalSize { suggestion, .. }) = warnings.first() else {
+    ///     panic!("1366 / 1.5 is not whole");
+    /// };
+    /// assert_eq!(*suggestion, Some(1.0));
+    ///
+    /// let relative: relative::Layout = "edp@1080p:1.25".parse()?;
+    /// let warnings = relative.to_absolute(&mut Offline::default())?.warnings;
+    /// assert!(warnings.is_empty());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    FractionalLogicalSize {
+        port: Port,
+        resolution: Size,
+        scale: f64,
+        suggestion: Option<f64>,
+    },
+    /// The screen is larger than [`Options::region`],
+    /// so it was left out.
+    TooLargeForRegion { port: Port, region: Size },
+    /// The screen is turned sideways,
+    /// and its resolution isn't supported,
+    /// but would be with width and height swapped.
+    /// Resolutions are meant before rotating,
+    /// see [`relative::Screen::resolution`],
+    /// so the swapped one was used instead.
+    /// `available` are all modes the screen supports.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{self, Mode, Output, OutputConfig},
+    /// #     comms::{self, Comms, Port},
+    /// #     convert::Warning,
+    /// #     geometry::Size,
+    /// #     info::Connector,
+    /// #     relative, Map,
+    /// # };
+    /// const DP: Port = Port { kind: Connector::DisplayPort, idx: 1 };
+    ///
+    /// struct Landscape;
+    /// impl Comms for Landscape {
+    ///     fn layout(&mut self) -> comms::Result<absolute::Layout> {
+    ///         let cfg = OutputConfig::default();
+    ///         Ok([Output { port: DP, cfg }].into_iter().collect())
+    ///     }
+    ///     fn modes(&mut self) -> comms::Result<Map<Port, Vec<Mode>>> {
+    ///         let size = Size { width: 1920, height: 1080 };
+    ///         Ok([(DP, vec![Mode { size, refresh: 60.0 }])].into())
+    ///     }
+    /// #   fn set_layout(&mut self, _: &absolute::Layout) -> comms::Result<()> {
+    /// #       Ok(())
+    /// #   }
+    /// }
+    ///
+    /// let relative: relative::Layout = "dp@1080x1920 #90".parse()?;
+    /// let converted = relative.to_absolute(&mut Landscape)?;
+    /// assert!(matches!(
+    ///     converted.warnings[..],
+    ///     [Warning::SidewaysMode { corrected: Size { width: 1920, height: 1080 }, .. }],
+    /// ));
+    /// let cfg = &converted.layout.outputs[&DP];
+    /// assert_eq!(cfg.resolution, Some(Size { width: 1920, height: 1080 }));
+    /// assert_eq!(cfg.bounds.size(), Size { width: 1080, height: 1920 });
+    ///
+    /// // unrotated, it's just wrong
+    /// let relative: relative::Layout = "dp@1080x1920".parse()?;
+    /// assert!(relative.to_absolute(&mut Landscape).is_err());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    SidewaysMode {
+        port: Port,
+        requested: Size,
+        corrected: Size,
+        available: Vec<absolute::Mode>,
+    },
+}
+
+/// How many pixels screens have at most in either direction,
+/// as of writing.
+/// Larger resolutions aren't rejected, but warned about.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{
+/// #     comms::offline::Offline,
+/// #     convert::{Options, Warning},
+/// #     relative,
+/// # };
+/// let relative: relative::Layout = "edp@1920x20000".parse()?;
+/// let warnings = relative.to_absolute_with(&mut Offline::default(), &Options::default())?.warnings;
+/// assert!(warnings
+///     .iter()
+///     .any(|warning| matches!(warning, Warning::ImplausibleResolution { .. })));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub const PLAUSIBLE_RESOLUTION: Pixel = 16384;
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Disconnected { port } => write!(f, "{port} is not connected, skipping it"),
+            Self::OriginMissing { port } => write!(
+                f,
+                "{port} should be at 0,0, but isn't in the layout, \
+                so the layout starts at 0,0 instead"
+            ),
+            Self::Audit(finding) => write!(f, "{finding}"),
+            Self::DpiMismatch { a, b, dpi_a, dpi_b } => write!(
+                f,
+                "{a} and {b} are next to each other, \
+                but their effective DPIs differ a lot ({dpi_a:.0} vs {dpi_b:.0}), \
+                so windows will change size when moved between them. \
+                Consider adjusting their scales."
+            ),
+            Self::ImplausibleResolution { port, size } => write!(
+                f,
+                "{port} should have a resolution of {size}, \
+                which is larger than any screen as of writing. \
+                Is there a typo?"
+            ),
+            Self::FractionalLogicalSize {
+                port,
+                resolution,
+                scale,
+                suggestion,
+            } => {
+                let width = f64::from(resolution.width) / scale;
+                let height = f64::from(resolution.height) / scale;
+                write!(
+                    f,
+                    "{port} would be {width:.3}x{height:.3} logical pixels large at scale {scale}, \
+                    which is {:.3}x{:.3} off from whole pixels, \
+                    so screens next to it may have gaps or overlap.",
+                    width - width.floor(),
+                    height - height.floor(),
+                )?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, " Consider scale {suggestion} instead.")?;
+                }
+                Ok(())
+            }
+            Self::TooLargeForRegion { port, region } => write!(
+                f,
+                "{port} is larger than the region of {region} it has to be in, skipping it"
+            ),
+            Self::SidewaysMode {
+                port,
+                requested,
+                corrected,
+                available,
+            } => write!(
+                f,
+                "{port} is turned sideways and can't be driven at {requested}, \
+                but at {corrected}, so using that instead. \
+                Resolutions are meant before rotating, available modes are {}.",
+                list(available),
+            ),
+        }
+    }
+}
+
+/// If `screen` is connected or not [optional].
+///
+/// [optional]: relative::Screen::optional
+fn is_wanted(screen: &Screen, current: &absolute::Layout) -> bool {
+    let port = screen.port();
+    let wanted = !screen.optional || current.outputs.contains_key(&port);
+    if !wanted {
+        log::verbose!("{port} is optional and not connected, skipping it");
+    }
+    wanted
+}
+
+/// Fails if any screen of `layout` is neither [optional] nor connected.
+/// Headless ones are created on demand, so they don't need to be.
+///
+/// [optional]: relative::Screen::optional
+fn check_connected(layout: &relative::Layout, current: &absolute::Layout) -> Result<()> {
+    let ports: Vec<_> = layout
+        .screens()
+        .filter(|screen| !screen.optional && screen.port().kind != Connector::Headless)
+        .map(|screen| screen.port())
+        .filter(|port| !current.outputs.contains_key(port))
+        .collect();
+
+    if ports.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Missing { ports })
+    }
+}
+
+/// Which resolution `screen` is going to have,
+/// in its native orientation, so before any rotation.
+/// [`None`] if it isn't connected and has no resolution specified.
+fn native_resolution(
+    screen: &Screen,
+    current: Option<&absolute::OutputConfig>,
+    modes: &Map<Port, Vec<absolute::Mode>>,
+) -> Result<Option<Size>> {
+    let size = match (screen.resolution, current) {
+        (Some(res), _) => res.size(),
+        (
+            None,
+            Some(absolute::OutputConfig {
+                resolution: Some(res),
+                ..
+            }),
+        ) => *res,
+        // the bounds are rotated and scaled by what's currently set,
+        // which isn't necessarily what's going to be set
+        (None, Some(cfg)) if !cfg.bounds.is_empty() => {
+            (cfg.bounds.size() * cfg.scale.unwrap_or(1.0)).rotate(cfg.transform.rotation)
+        }
+        // connected, but no current mode to keep
+        // (e.g. disabled or just woke up), so try the preferred one instead
+        (None, Some(_)) => preferred_size(screen.port(), modes)?,
+        (None, None) => return Ok(None),
+    };
+    Ok(Some(size))
+}
+
+/// Warns if `screen` should have a resolution larger than [`PLAUSIBLE_RESOLUTION`].
+fn implausible_resolution(screen: &Screen) -> Option<Warning> {
+    let size = screen.resolution?.size();
+    (size.width.max(size.height) > PLAUSIBLE_RESOLUTION).then_some(Warning::ImplausibleResolution {
+        port: screen.port(),
+        size,
+    })
+}
+
+fn list(items: &[impl fmt::Display]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
 impl relative::Layout {
     /// Resolve the layout according to the currently connected displays.
-    pub fn to_absolute(&self, comms: &mut dyn Comms) -> comms::Result<absolute::Layout> {
-        let mut placed = absolute::Layout::new();
+    ///
+    /// If a screen doesn't specify a resolution,
+    /// the current one is kept.
+    /// If it has none (e.g. since it's disabled),
+    /// the preferred mode is used instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{self, Mode, Output, OutputConfig},
+    /// #     comms::{self, Comms, Port},
+    /// #     geometry::Size,
+    /// #     info::Connector,
+    /// #     relative, Map,
+    /// # };
+    /// const EDP: Port = Port { kind: Connector::Edp, idx: 1 };
+    ///
+    /// // connected, but currently without mode, e.g. just after resume
+    /// struct Sleepy;
+    /// impl Comms for Sleepy {
+    ///     fn layout(&mut self) -> comms::Result<absolute::Layout> {
+    ///         let cfg = OutputConfig::default();
+    ///         Ok([Output { port: EDP, cfg }].into_iter().collect())
+    ///     }
+    ///     fn modes(&mut self) -> comms::Result<Map<Port, Vec<Mode>>> {
+    ///         let size = Size { width: 2256, height: 1504 };
+    ///         Ok([(EDP, vec![Mode { size, refresh: 60.0 }])].into())
+    ///     }
+    /// #   fn set_layout(&mut self, _: &absolute::Layout) -> comms::Result<()> {
+    /// #       Ok(())
+    /// #   }
+    /// }
+    ///
+    /// let relative: relative::Layout = "edp".parse()?;
+    /// let layout = relative.to_absolute(&mut Sleepy)?.layout;
+    /// assert_eq!(
+    ///     layout.outputs[&EDP].bounds.size(),
+    ///     Size { width: 2256, height: 1504 },
+    /// );
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn to_absolute(&self, comms: &mut dyn Comms) -> Result<Converted> {
+        self.to_absolute_with(comms, &Options::default())
+    }
+
+    /// Like [`relative::Layout::to_absolute`],
+    /// but with control over the conversion through `opts`.
+    pub fn to_absolute_with(&self, comms: &mut dyn Comms, opts: &Options) -> Result<Converted> {
+        Context::fetch(comms)?.convert(self, opts)
+    }
+
+    /// This layout with the [others] screen, if any,
+    /// replaced by one screen for each output in `current`
+    /// which isn't mentioned anywhere else, ordered by port.
+    ///
+    /// [others]: relative::Target::Others
+    #[must_use]
+    pub fn with_others_expanded(&self, current: &absolute::Layout) -> Cow<'_, Self> {
+        if !self.screens().any(|screen| screen.target == Target::Others) {
+            return Cow::Borrowed(self);
+        }
+
+        let mentioned: Vec<_> = self
+            .screens()
+            .filter_map(|screen| match screen.target {
+                Target::Port(port) => Some(port),
+                Target::Others => None,
+            })
+            .collect();
+        let others: Vec<_> = current
+            .outputs
+            .keys()
+            .filter(|port| !mentioned.contains(port))
+            .copied()
+            .collect();
+
+        let expand = |screen: &Screen| -> Vec<Screen> {
+            if screen.target != Target::Others {
+                return vec![screen.clone()];
+            }
+
+            others
+                .iter()
+                .enumerate()
+                .map(|(i, &port)| {
+                    let mut expanded = Screen {
+                        target: Target::Port(port),
+                        ..screen.clone()
+                    };
+                    // the first one is where the others continue from
+                    if i > 0 {
+                        expanded.pos = None;
+                        expanded.anchor = None;
+                        expanded.offset = None;
+                        expanded.at = None;
+                    }
+                    expanded
+                })
+                .collect()
+        };
+
+        Cow::Owned(relative::Layout {
+            groups: self
+                .groups
+                .iter()
+                .map(|group| group.iter().flat_map(expand).collect())
+                .collect(),
+            flow: self.flow,
+        })
+    }
+}
+
+/// What the WM knows about its outputs, as far as conversion needs it.
+/// Can be kept around to convert multiple times without asking the WM again,
+/// e.g. when trying out several layouts or on every hotplug.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{
+/// #     absolute::{Layout, Mode, Output, OutputConfig},
+/// #     comms::Port,
+/// #     convert::{Context, Options},
+/// #     geometry::Size,
+/// #     info::Connector,
+/// #     relative,
+/// # };
+/// let port = Port { kind: Connector::DisplayPort, idx: 1 };
+/// let size = Size { width: 1920, height: 1080 };
+/// let modes = [(port, vec![Mode { size, refresh: 60.0 }])].into();
+/// let current = [Output { port, cfg: OutputConfig::default() }].into_iter().collect();
+/// let ctx = Context::new(current, modes);
+///
+/// for desc in ["dp", "dp:2"] {
+///     let relative: relative::Layout = desc.parse()?;
+///     let converted = ctx.convert(&relative, &Options::default())?;
+///     assert!(converted.layout.outputs.contains_key(&port));
+/// }
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Context {
+    current: absolute::Layout,
+    modes: Map<Port, Vec<absolute::Mode>>,
+}
+
+impl Context {
+    /// Uses the given state instead of asking a WM.
+    #[must_use]
+    pub fn new(current: absolute::Layout, modes: Map<Port, Vec<absolute::Mode>>) -> Self {
+        Self { current, modes }
+    }
+
+    /// Asks `comms` for its current layout and all modes.
+    pub fn fetch(comms: &mut dyn Comms) -> Result<Self> {
+        Ok(Self::new(comms.layout()?, comms.modes()?))
+    }
+
+    /// Asks `comms` for its current layout again.
+    /// The modes an output supports don't change while the same screen is connected,
+    /// so they're only asked for again if outputs were connected or disconnected
+    /// or a different screen is plugged into a port now.
+    pub fn refresh(&mut self, comms: &mut dyn Comms) -> Result<()> {
         let current = comms.layout()?;
-        let mut bb = Rect::default();
-
-        for screen in &self.screens {
-            // TODO: this manual merging logic is a bit strenous.
-            // maybe this could be done shorter somehow?
-            let screen_in_sway = current.outputs.get(&screen.port);
-
-            let scale = screen
-                .scale
-                .or_else(|| screen_in_sway.map(|cfg| cfg.scale))
-                .unwrap_or(1.0);
-
-            let resolution = screen
-                .resolution
-                .map(|res| res.size())
-                .or_else(|| screen_in_sway.map(|cfg| cfg.bounds.size() * scale));
-            let Some(resolution) = resolution else {
-                // user specified screen that isn't connected
-                // hence should not affect layout
+        let screens = |layout: &absolute::Layout| {
+            layout
+                .outputs
+                .iter()
+                .map(|(port, cfg)| (*port, cfg.identity.clone()))
+                .collect::<Vec<_>>()
+        };
+        if screens(&current) != screens(&self.current) {
+            self.modes = comms.modes()?;
+        }
+        self.current = current;
+
+        Ok(())
+    }
+
+    /// The layout of the WM as last fetched.
+    #[must_use]
+    pub fn current(&self) -> &absolute::Layout {
+        &self.current
+    }
+
+    /// Like [`Self::convert`], but collects as many errors as possible
+    /// instead of stopping at the first one, so they can all be fixed at once.
+    ///
+    /// The mode of each screen is checked on its own
+    /// against what its output supports.
+    /// Everything else is found by converting, which stops at its first error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{Mode, Output, OutputConfig},
+    /// #     comms::Port,
+    /// #     convert::{Context, Options},
+    /// #     geometry::Size,
+    /// #     info::Connector,
+    /// #     relative,
+    /// # };
+    /// let dp = Port { kind: Connector::DisplayPort, idx: 1 };
+    /// let hdmi = Port { kind: Connector::HdmiA, idx: 1 };
+    /// let fhd = vec![Mode { size: Size { width: 1920, height: 1080 }, refresh: 60.0 }];
+    /// let modes = [(dp, fhd.clone()), (hdmi, fhd)].into();
+    /// let current = [dp, hdmi]
+    ///     .into_iter()
+    ///     .map(|port| Output { port, cfg: OutputConfig::default() })
+    ///     .collect();
+    /// let ctx = Context::new(current, modes);
+    ///
+    /// let relative: relative::Layout = "dp@4k + hdmi@1080p*144 + edp".parse()?;
+    /// let validation = ctx.validate(&relative, &Options::default());
+    /// assert_eq!(validation.errors.len(), 2);
+    /// // edp isn't connected
+    /// assert_eq!(validation.warnings.len(), 1);
+    ///
+    /// let relative: relative::Layout = "dp@1080p + hdmi".parse()?;
+    /// assert!(ctx.validate(&relative, &Options::default()).errors.is_empty());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn validate(&self, layout: &relative::Layout, opts: &Options) -> Validation {
+        let layout = &*layout.with_others_expanded(&self.current);
+        let mut errors = Vec::new();
+        let mut modes = self.modes.clone();
+        for screen in layout.screens() {
+            let (Some(res), Some(available)) = (screen.resolution, self.modes.get(&screen.port()))
+            else {
                 continue;
             };
+            let rotation = screen
+                .transform
+                .or_else(|| {
+                    self.current
+                        .outputs
+                        .get(&screen.port())
+                        .map(|cfg| cfg.transform)
+                })
+                .unwrap_or_default()
+                .rotation;
+            // converting warns about sideways ones on its own
+            if let Err(err) = supported_mode(screen, res.size(), rotation, available) {
+                errors.push(err);
+                // already reported, so converting shouldn't stop there
+                modes.remove(&screen.port());
+            }
+        }
 
-            // Which size the screen occupies in the *layout*, not physically.
-            // See the manual page of sway-output for why the scale division is done.
-            // In short: For positioning, the scale has to be taken into account.
-            // So if screen A has scale 2 and has a resolution of 800x600,
-            // and we wanted to place screen B right next to it,
-            // we'd need to place it at 400x0 (since the scale is 2, and 800 / 2 = 400).
-            // In our case, that just means dividing the size of the bounds by the scale,
-            // then using it accordingly in the bounding box.
-            let layout_size = resolution.rotate(screen.transform.rotation) / scale;
-
-            // note: order of x/y placement does not actually matter
-            // they don't have any influence on each other
-            let bounds = match screen.pos {
-                // place left/right of bbox, then decide exact vertical placement
-                Position::Hori { edge, spec } => Rect {
-                    x: bb.x.place_outside(layout_size.width, edge.into()),
-                    y: bb.y.place_inside(layout_size.height, spec.map(Into::into)),
-                },
-                // place top/bottom of bbox, then decide exact horizontal placement
-                Position::Vert { edge, spec } => Rect {
-                    x: bb.x.place_inside(layout_size.width, spec.map(Into::into)),
-                    y: bb.y.place_outside(layout_size.height, edge.into()),
-                },
-            };
+        let rest = Self::new(self.current.clone(), modes);
+        let warnings = match rest.convert(layout, opts) {
+            Ok(converted) => converted.warnings,
+            Err(err) => {
+                errors.push(err);
+                Vec::new()
+            }
+        };
+
+        Validation { errors, warnings }
+    }
+
+    /// Like [`relative::Layout::to_absolute_with`],
+    /// but using the state in this context instead of asking the WM.
+    pub fn convert(&self, layout: &relative::Layout, opts: &Options) -> Result<Converted> {
+        let Self { current, modes } = self;
+        let layout = &*layout.with_others_expanded(current);
+        if opts.strict {
+            check_connected(layout, current)?;
+        }
+
+        let mut placed = absolute::Layout::new();
+        let mut warnings = Vec::new();
+        let mut bounding_boxes = Vec::new();
+
+        let default_pos = layout.flow.map_or(opts.default_pos, Flow::default_pos);
+
+        let is_exact = |group: &[Screen]| group.iter().any(|screen| screen.at.is_some());
+        for group in &layout.groups {
+            let exact = is_exact(group);
+            let mut group = place_group(
+                group,
+                current,
+                modes,
+                default_pos,
+                opts,
+                &mut warnings,
+                &mut bounding_boxes,
+            )?;
+            // tile the groups from left to right, so they don't collide,
+            // except where the user asked for exact positions
+            if opts.reset_to_origin && !exact {
+                group.reset_to_origin();
+            }
+            if !placed.outputs.is_empty() && !exact {
+                group.translate(Point {
+                    x: placed.bounding_box().x.end() - group.bounding_box().x.start(),
+                    y: 0,
+                });
+            }
+
+            placed.outputs.append(&mut group.outputs);
+        }
+
+        if let Some(Flow::Stack(spec)) = layout.flow {
+            placed.align_column(spec);
+        }
+        if let Some(spec) = opts.align_rows {
+            placed.align_row(spec);
+        }
+
+        if opts.reset_to_origin && !layout.groups.iter().any(|group| is_exact(group)) {
+            placed.reset_to_origin();
+        }
+        if let Some(port) = opts.origin {
+            if placed.outputs.contains_key(&port) {
+                placed.anchor_port_at_origin(port);
+            } else {
+                warnings.push(Warning::OriginMissing { port });
+            }
+        }
+        if let Some(region) = opts.region {
+            let left_out = placed.clamp_into(region);
+            warnings.extend(left_out.into_iter().map(|port| Warning::TooLargeForRegion {
+                port,
+                region: region.size(),
+            }));
+        }
+        placed.complete = current
+            .outputs
+            .keys()
+            .all(|port| placed.outputs.contains_key(port));
+
+        // the groups and the origin moved things around, so check again
+        for output in placed.outputs() {
+            check_bounds(*output.port, output.cfg.bounds, opts.max_coordinate)?;
+        }
+
+        let bb = placed.bounding_box();
+        log::verbose!("Whole layout is {} large", bb.size());
+
+        warnings.extend(audit(&placed, opts.max_coordinate));
+        warnings.extend(dpi_mismatches(&placed));
+
+        Ok(Converted {
+            layout: placed,
+            warnings,
+            bounding_boxes,
+        })
+    }
+}
+
+/// Where [`Options::around_internal`] places the first screen after the internal one.
+const ABOVE_INTERNAL: Position = Position::Vert {
+    edge: Vert::Top,
+    spec: HoriSpec::Center,
+    corner: None,
+};
+
+/// How many Hz a refresh rate may be off,
+/// since modes are usually listed like 59.951 Hz, while 60 is meant.
+pub(crate) const REFRESH_TOLERANCE: f64 = 0.5;
+
+/// Places the given screens relative to each other,
+/// starting with an empty bounding box.
+fn place_group(
+    screens: &[Screen],
+    current: &absolute::Layout,
+    modes: &Map<Port, Vec<absolute::Mode>>,
+    default_pos: Position,
+    opts: &Options,
+    warnings: &mut Vec<Warning>,
+    bounding_boxes: &mut Vec<(Port, Rect)>,
+) -> Result<absolute::Layout> {
+    let Options {
+        rounding,
+        all_scale,
+        max_coordinate,
+        around_internal,
+        wrap_width,
+        ..
+    } = *opts;
+
+    let mut placed = absolute::Layout::new();
+    let mut bb = Rect::default();
+
+    // set after placing the internal screen,
+    // until a screen without position makes use of it
+    let mut above_internal = None;
+    let mut rows = Rows::new(wrap_width);
 
-            // now that we've got the screen bounds, make sure it's actually noticed
-            // by the bounding box
-            // so future screens can be placed accordingly
+    let order: Vec<_> = placement_order(screens, current, around_internal).collect();
+    for (n, &(i, screen)) in order.iter().enumerate() {
+        let port = screen.port();
+        // TODO: this manual merging logic is a bit strenous.
+        // maybe this could be done shorter somehow?
+        let screen_in_sway = current.outputs.get(&port);
+
+        let explicit_scale = explicit_scale(screen, all_scale, &placed, current)?;
+        let scale = explicit_scale
+            .or_else(|| screen_in_sway.and_then(|cfg| cfg.scale))
+            .unwrap_or(1.0);
+
+        let transform = screen
+            .transform
+            .or_else(|| screen_in_sway.map(|cfg| cfg.transform))
+            .unwrap_or_default();
+
+        warnings.extend(implausible_resolution(screen));
+        let Some(mut resolution) = native_resolution(screen, screen_in_sway, modes)? else {
+            // user specified screen that isn't connected
+            // hence should not affect layout
+            warnings.push(Warning::Disconnected { port });
+            continue;
+        };
+
+        // sway would silently ignore modes the screen doesn't support,
+        // so better tell now
+        if let (Some(res), Some(available)) = (screen.resolution, modes.get(&port)) {
+            if let Some(swapped) =
+                supported_mode(screen, res.size(), transform.rotation, available)?
+            {
+                let mut available = available.clone();
+                available.dedup();
+                warnings.push(Warning::SidewaysMode {
+                    port,
+                    requested: res.size(),
+                    corrected: swapped,
+                    available,
+                });
+                resolution = swapped;
+            }
+        }
+
+        // keeping the current refresh rate only makes sense
+        // if the resolution is kept as well
+        let refresh = screen.refresh.or_else(|| {
+            screen_in_sway
+                .and_then(|cfg| cfg.refresh)
+                .filter(|_| screen.resolution.is_none())
+        });
+
+        check_logical_size(port, resolution, scale, rounding)?;
+        warnings.extend(fractional_logical_size(port, resolution, scale));
+
+        // Which size the screen occupies in the *layout*, not physically.
+        // See the manual page of sway-output for why the scale division is done.
+        // In short: For positioning, the scale has to be taken into account.
+        // So if screen A has scale 2 and has a resolution of 800x600,
+        // and we wanted to place screen B right next to it,
+        // we'd need to place it at 400x0 (since the scale is 2, and 800 / 2 = 400).
+        // In our case, that just means dividing the size of the bounds by the scale,
+        // then using it accordingly in the bounding box.
+        let layout_size = resolution
+            .rotate(transform.rotation)
+            .scaled_logical(scale, rounding);
+
+        // wrapping only makes sense for screens that go wherever there's space
+        let flowing = screen.pos.is_none() && screen.at.is_none() && above_internal.is_none();
+        let reference = match screen.anchor {
+            Some(anchor) => resolve_anchor(port, anchor, &screens[..i], &order[..n], &placed, bb)?,
+            None if flowing => rows.reference(bb),
+            None => bb,
+        };
+
+        let physical_size = screen_in_sway.and_then(|cfg| cfg.physical_size);
+        let offset = resolve_offset(screen, layout_size, transform.rotation, physical_size)?;
+        let bounds = bounds_of(screen, layout_size, offset, reference, || {
+            above_internal.take().unwrap_or(default_pos)
+        });
+        let bounds = rows.fit(bounds, bb, flowing && !placed.outputs.is_empty());
+        if around_internal && port.kind.is_internal() {
+            above_internal = Some(ABOVE_INTERNAL);
+        }
+
+        // better stop before the bounding box calculations overflow
+        check_bounds(port, bounds, max_coordinate)?;
+
+        // now that we've got the screen bounds, make sure it's actually noticed
+        // by the bounding box
+        // so future screens can be placed accordingly
+        // (starting from the first one, an exact position might be far off 0,0)
+        if placed.outputs.is_empty() {
+            bb = bounds;
+        } else {
             bb.stretch_to_rect(bounds);
+        }
+        bounding_boxes.push((port, bb));
+
+        log::verbose!(
+            "Placing {} with size {layout_size} at {},{} (relative to its group)",
+            port,
+            bounds.x.start(),
+            bounds.y.start(),
+        );
 
-            // that'd be it! let's actually place the output screen
-            // we just calculated the bounds of
-            placed.add(absolute::Output {
-                port: screen.port,
-                cfg: absolute::OutputConfig {
-                    bounds,
-                    scale,
-                    resolution: Some(resolution),
-                    transform: screen.transform,
-                    active: true,
+        // that'd be it! let's actually place the output screen
+        // we just calculated the bounds of
+        placed.add(absolute::Output {
+            port,
+            cfg: absolute::OutputConfig {
+                bounds,
+                scale: Some(scale),
+                resolution: Some(resolution),
+                refresh,
+                refresh_range: screen.refresh_range,
+                transform,
+                active: true,
+                max_render_time: screen.max_render_time,
+                subpixel: screen.subpixel,
+                render_bit_depth: screen.render_bit_depth,
+                power: screen.power,
+                background: screen.background.clone(),
+                physical_size: screen_in_sway.and_then(|cfg| cfg.physical_size),
+                identity: screen_in_sway.and_then(|cfg| cfg.identity.clone()),
+                // headless outputs are purely virtual, so they can be just made up
+                create: port.kind == Connector::Headless && screen_in_sway.is_none(),
+                // everything else is just kept as-is anyway,
+                // so no need to send it
+                explicit: absolute::Explicit {
+                    position: true,
+                    resolution: screen.resolution.is_some() || screen.refresh.is_some(),
+                    scale: explicit_scale.is_some(),
+                    transform: screen.transform.is_some(),
+                    // it's active, so there's nothing to disable
+                    active: false,
                 },
-            });
+            },
+        });
+    }
+
+    Ok(placed)
+}
+
+/// The row screens are currently added to
+/// when wrapping at [`Options::wrap_width`].
+struct Rows {
+    max_width: Option<Pixel>,
+    current: Rect,
+}
+
+impl Rows {
+    fn new(max_width: Option<Pixel>) -> Self {
+        Self {
+            max_width,
+            current: Rect::default(),
         }
+    }
 
-        placed.reset_to_origin();
+    /// Where a new row starts.
+    const NEW_ROW: Position = Position::Vert {
+        edge: Vert::Bottom,
+        spec: HoriSpec::Extreme(Hori::Left),
+        corner: None,
+    };
+
+    /// What a screen without position of its own is placed next to,
+    /// given the bounding box `bb` of all screens so far.
+    fn reference(&self, bb: Rect) -> Rect {
+        if self.max_width.is_some() {
+            self.current
+        } else {
+            bb
+        }
+    }
+
+    /// Moves `bounds` into a new row below `bb`
+    /// if it's `flowing` and would make the layout too wide,
+    /// then remembers the row it ended up in.
+    fn fit(&mut self, bounds: Rect, bb: Rect, flowing: bool) -> Rect {
+        let Some(max_width) = self.max_width.filter(|_| flowing) else {
+            self.current = bounds;
+            return bounds;
+        };
+
+        let mut widened = bb;
+        widened.stretch_to_rect(bounds);
+        if widened.x.len() <= max_width {
+            self.current.stretch_to_rect(bounds);
+            return bounds;
+        }
+
+        let wrapped = place_next_to(bb, bounds.size(), Self::NEW_ROW);
+        log::verbose!("Starting a new row, the layout would be wider than {max_width} otherwise");
+        self.current = wrapped;
+        wrapped
+    }
+}
+
+/// Where `screen` with `size` ends up when placed next to `reference`,
+/// using the position from `fallback` if it doesn't specify one at all.
+fn bounds_of(
+    screen: &Screen,
+    size: Size,
+    offset: Point,
+    reference: Rect,
+    fallback: impl FnOnce() -> Position,
+) -> Rect {
+    if let Some(at) = screen.at {
+        return Rect {
+            x: Interval::new(at.x, at.x + size.width),
+            y: Interval::new(at.y, at.y + size.height),
+        };
+    }
+
+    let pos = screen.pos.unwrap_or_else(fallback);
+    place_next_to(reference, size, pos) + offset
+}
+
+/// How far `screen` is moved after placing it, in logical pixels,
+/// given that it occupies `layout_size` in the layout
+/// and is `physical_size` large in reality, before `rotation`.
+///
+/// Physical lengths are only as exact as the physical size the screen reports,
+/// which is usually rounded to whole millimeters or even centimeters.
+fn resolve_offset(
+    screen: &Screen,
+    layout_size: Size,
+    rotation: Rotation,
+    physical_size: Option<PhysicalSize>,
+) -> Result<Point> {
+    let Some(offset) = screen.offset else {
+        return Ok(Point { x: 0, y: 0 });
+    };
+
+    let physical = physical_size.map(|physical| {
+        let (width, height) = (f64::from(physical.width), f64::from(physical.height));
+        match rotation {
+            Rotation::Quarter | Rotation::ThreeQuarter => (height, width),
+            Rotation::None | Rotation::Half => (width, height),
+        }
+    });
+    let per_mm =
+        |pixels: Pixel, mm: Option<f64>| mm.filter(|&mm| mm > 0.0).map(|mm| f64::from(pixels) / mm);
+    let x_per_mm = per_mm(layout_size.width, physical.map(|(width, _)| width));
+    let y_per_mm = per_mm(layout_size.height, physical.map(|(_, height)| height));
+
+    let port = screen.port();
+    let unknown = || Error::UnknownPhysicalSize { port };
+    Ok(Point {
+        x: offset.x.to_pixels(x_per_mm).ok_or_else(unknown)?,
+        y: offset.y.to_pixels(y_per_mm).ok_or_else(unknown)?,
+    })
+}
+
+/// Where something of `size` ends up when placed at `pos` of `reference`.
+fn place_next_to(reference: Rect, size: Size, pos: Position) -> Rect {
+    // note: order of x/y placement does not actually matter
+    // they don't have any influence on each other
+    match pos {
+        // the chosen corner goes onto the point on the edge
+        Position::Hori {
+            edge,
+            spec,
+            corner: Some(corner),
+        } => {
+            let at = Point {
+                x: reference.x.point_at(MaybeCenter::Extreme(edge.into())),
+                y: reference.y.point_at(spec.map(Into::into)),
+            };
+            Rect::with_corner_at(corner, at, size)
+        }
+        Position::Vert {
+            edge,
+            spec,
+            corner: Some(corner),
+        } => {
+            let at = Point {
+                x: reference.x.point_at(spec.map(Into::into)),
+                y: reference.y.point_at(MaybeCenter::Extreme(edge.into())),
+            };
+            Rect::with_corner_at(corner, at, size)
+        }
+        // place left/right of reference, then decide exact vertical placement
+        Position::Hori {
+            edge,
+            spec,
+            corner: None,
+        } => Rect {
+            x: reference.x.place_outside(size.width, edge.into()),
+            y: reference.y.place_inside(size.height, spec.map(Into::into)),
+        },
+        // place top/bottom of reference, then decide exact horizontal placement
+        Position::Vert {
+            edge,
+            spec,
+            corner: None,
+        } => Rect {
+            x: reference.x.place_inside(size.width, spec.map(Into::into)),
+            y: reference.y.place_outside(size.height, edge.into()),
+        },
+    }
+}
+
+/// The screens with an exact position before all others,
+/// so the others can be placed around them no matter where they're listed.
+/// If placing `around_internal`, the first internal screen comes right after them.
+/// Otherwise, they're ordered by [`Screen::order`], then how they're listed.
+/// Optional screens which aren't connected are left out.
+/// Indices refer to `screens`.
+fn placement_order<'a>(
+    screens: &'a [Screen],
+    current: &absolute::Layout,
+    around_internal: bool,
+) -> impl Iterator<Item = (usize, &'a Screen)> {
+    let (mut explicit, mut relative): (Vec<_>, Vec<_>) = screens
+        .iter()
+        .enumerate()
+        .filter(|(_, screen)| is_wanted(screen, current))
+        .partition(|(_, screen)| screen.at.is_some());
+    // stable, so the listed order is kept otherwise
+    for screens in [&mut explicit, &mut relative] {
+        screens.sort_by_key(|(_, screen)| screen.order.unwrap_or_default());
+    }
 
-        Ok(placed)
+    let internal = relative
+        .iter()
+        .position(|(_, screen)| screen.port().kind.is_internal())
+        .filter(|_| around_internal);
+    if let Some(idx) = internal {
+        let internal = relative.remove(idx);
+        relative.insert(0, internal);
     }
+
+    explicit.into_iter().chain(relative)
+}
+
+/// Size of the mode the WM prefers for `port`.
+fn preferred_size(port: Port, modes: &Map<Port, Vec<absolute::Mode>>) -> Result<Size> {
+    modes
+        .get(&port)
+        .and_then(|modes| modes.first())
+        .map(|mode| mode.size)
+        .ok_or(Error::NoMode { port })
+}
+
+/// The scale `screen` asks for, either directly or like another screen.
+/// [`None`] if it doesn't ask for any, and `all_scale` doesn't either.
+fn explicit_scale(
+    screen: &Screen,
+    all_scale: Option<f64>,
+    placed: &absolute::Layout,
+    current: &absolute::Layout,
+) -> Result<Option<f64>> {
+    let (Some(like), None) = (screen.scale_like, all_scale.or(screen.scale)) else {
+        return Ok(all_scale.or(screen.scale));
+    };
+
+    // the one it's placed as has precedence over whatever the WM has right now
+    let scale = [placed, current]
+        .into_iter()
+        .find_map(|layout| layout.outputs.get(&like)?.scale)
+        .ok_or(Error::UnknownScale {
+            port: screen.port(),
+            like,
+        })?;
+    Ok(Some(scale))
+}
+
+/// What the screen at `port` anchored at `anchor` is placed next to:
+/// The bounds of the anchor if it's been `placed` already,
+/// otherwise `bb`, since it has been skipped for not being connected.
+/// Fails if the anchor is neither `listed` nor `ordered` before the screen.
+fn resolve_anchor(
+    port: Port,
+    anchor: Port,
+    listed: &[Screen],
+    ordered: &[(usize, &Screen)],
+    placed: &absolute::Layout,
+    bb: Rect,
+) -> Result<Rect> {
+    let mut before = listed
+        .iter()
+        .chain(ordered.iter().map(|(_, screen)| *screen));
+    if !before.any(|screen| screen.port() == anchor) {
+        return Err(Error::UnknownAnchor { port, anchor });
+    }
+
+    Ok(placed.outputs.get(&anchor).map_or(bb, |cfg| cfg.bounds))
+}
+
+/// Everything [`absolute::Layout::audit`] finds about `layout`
+/// that placing it didn't already report.
+fn audit(layout: &absolute::Layout, max: Pixel) -> impl Iterator<Item = Warning> {
+    layout
+        .audit(max)
+        .into_iter()
+        .filter(|finding| {
+            // already checked while placing, with the actual options
+            !matches!(
+                finding.kind,
+                AuditKind::FractionalLogicalSize { .. } | AuditKind::OutOfBounds { .. }
+            )
+        })
+        .map(Warning::Audit)
+}
+
+/// All pairs of neighboring outputs which differ a lot in their effective DPI.
+fn dpi_mismatches(layout: &absolute::Layout) -> impl Iterator<Item = Warning> + '_ {
+    /// How many times larger the effective DPI of one output
+    /// may be than the one of its neighbor before warning.
+    const MAX_RATIO: f64 = 1.5;
+
+    // the scale is what counteracts the DPI, hence divide by it
+    let effective_dpi = |cfg: &absolute::OutputConfig| Some(cfg.dpi()? / cfg.scale?);
+
+    layout.neighbors().filter_map(move |(a, b)| {
+        let dpi_a = effective_dpi(a.cfg)?;
+        let dpi_b = effective_dpi(b.cfg)?;
+
+        (dpi_a.max(dpi_b) / dpi_a.min(dpi_b) > MAX_RATIO).then_some(Warning::DpiMismatch {
+            a: *a.port,
+            b: *b.port,
+            dpi_a,
+            dpi_b,
+        })
+    })
+}
+
+/// Makes sure that `bounds` stay within `max` in every direction.
+pub(crate) fn check_bounds(port: Port, bounds: Rect, max: Pixel) -> Result<()> {
+    let within = |interval: Interval| interval.start() >= -max && interval.end() <= max;
+
+    if within(bounds.x) && within(bounds.y) {
+        Ok(())
+    } else {
+        Err(Error::OutOfBounds { port, bounds, max })
+    }
+}
+
+/// Makes sure that `resolution` divided by `scale`
+/// is at least one pixel large, but still fits into [`Pixel`],
+/// as otherwise the bounds would silently be nonsense.
+fn check_logical_size(port: Port, resolution: Size, scale: f64, rounding: Rounding) -> Result<()> {
+    let usable = |len: Pixel| {
+        let logical = rounding.apply(f64::from(len) / scale);
+        // also rejects NaN and infinities
+        (1.0..=f64::from(Pixel::MAX)).contains(&logical)
+    };
+
+    if usable(resolution.width) && usable(resolution.height) {
+        Ok(())
+    } else {
+        Err(Error::DegenerateSize {
+            port,
+            resolution,
+            scale,
+        })
+    }
+}
+
+/// Warns if `resolution` divided by `scale` isn't whole.
+pub(crate) fn fractional_logical_size(port: Port, resolution: Size, scale: f64) -> Option<Warning> {
+    let whole = |len: Pixel| {
+        let logical = f64::from(len) / scale;
+        (logical - logical.round()).abs() < 1e-6
+    };
+    if whole(resolution.width) && whole(resolution.height) {
+        return None;
+    }
+
+    Some(Warning::FractionalLogicalSize {
+        port,
+        resolution,
+        scale,
+        suggestion: evenly_dividing_scale(resolution, scale),
+    })
+}
+
+/// The scale closest to `scale` that divides `resolution` evenly,
+/// at most half off.
+/// Only multiples of 1/120 are considered,
+/// since that's what Wayland clients can be told about.
+/// On a tie, the smaller one wins.
+#[allow(clippy::cast_possible_truncation)] // scales are nowhere near that large
+fn evenly_dividing_scale(resolution: Size, scale: f64) -> Option<f64> {
+    const DENOMINATOR: i32 = 120;
+
+    let divides = |numerator: i32| {
+        let divides_len =
+            |len: Pixel| (i64::from(len) * i64::from(DENOMINATOR)) % i64::from(numerator) == 0;
+        numerator > 0 && divides_len(resolution.width) && divides_len(resolution.height)
+    };
+
+    let closest = (scale * f64::from(DENOMINATOR)).round() as i32;
+    (0..=DENOMINATOR / 2)
+        .flat_map(|offset| {
+            [
+                closest.saturating_sub(offset),
+                closest.saturating_add(offset),
+            ]
+        })
+        .find(|&numerator| divides(numerator))
+        .map(|numerator| f64::from(numerator) / f64::from(DENOMINATOR))
+}
+
+/// The resolution of `screen` with width and height swapped,
+/// if it's turned sideways by `rotation`
+/// and only the swapped resolution is among the `available` modes.
+/// Then the resolution was most likely given as it looks after rotating,
+/// while it's meant before.
+fn sideways_mode(
+    screen: &Screen,
+    rotation: Rotation,
+    available: &[absolute::Mode],
+) -> Option<Size> {
+    let size = screen.resolution?.size();
+    let swapped = size.rotate(rotation);
+    let supported = |size| check_mode(screen.port(), size, screen.refresh, available).is_ok();
+
+    (swapped != size && !supported(size) && supported(swapped)).then_some(swapped)
+}
+
+/// Makes sure that `screen` can be driven at `size`, its requested resolution,
+/// like [`check_mode`], but also accepts it if it's turned sideways by `rotation`
+/// and the resolution is only available swapped, see [`sideways_mode`].
+/// In that case, the swapped resolution is returned, which is to be used instead.
+fn supported_mode(
+    screen: &Screen,
+    size: Size,
+    rotation: Rotation,
+    available: &[absolute::Mode],
+) -> Result<Option<Size>> {
+    if let Some(swapped) = sideways_mode(screen, rotation, available) {
+        return Ok(Some(swapped));
+    }
+    check_mode(screen.port(), size, screen.refresh, available)?;
+    Ok(None)
+}
+
+/// Makes sure that one of the `available` modes has the given size,
+/// and the given refresh rate if any.
+/// If nothing is available at all, the modes are probably just unknown,
+/// so anything goes.
+fn check_mode(
+    port: Port,
+    size: Size,
+    refresh: Option<f64>,
+    available: &[absolute::Mode],
+) -> Result<()> {
+    const MAX_CLOSEST: usize = 3;
+
+    let refresh_off = |mode: &absolute::Mode| refresh.map_or(0.0, |hz| (mode.refresh - hz).abs());
+
+    if available.is_empty()
+        || available
+            .iter()
+            .any(|mode| mode.size == size && refresh_off(mode) <= REFRESH_TOLERANCE)
+    {
+        return Ok(());
+    }
+
+    // prefer the same size with a different refresh rate,
+    // then the ones with the most similar amount of pixels
+    let area = |size: Size| i64::from(size.width) * i64::from(size.height);
+    let mut closest = available.to_vec();
+    closest.sort_by(|a, b| {
+        (a.size != size)
+            .cmp(&(b.size != size))
+            .then_with(|| {
+                (area(a.size) - area(size))
+                    .abs()
+                    .cmp(&(area(b.size) - area(size)).abs())
+            })
+            .then_with(|| refresh_off(a).total_cmp(&refresh_off(b)))
+    });
+    // some screens list the same mode multiple times
+    closest.dedup();
+    closest.truncate(MAX_CLOSEST);
+
+    let mut requested = size.to_string();
+    if let Some(hz) = refresh {
+        write!(requested, "@{hz}Hz").unwrap();
+    }
+
+    Err(Error::UnsupportedMode {
+        port,
+        requested,
+        closest,
+    })
 }
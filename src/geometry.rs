@@ -7,10 +7,28 @@ use std::{
     ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign},
 };
 
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
 pub type Pixel = i32;
 
 /// Rectangle in pixels.
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+)]
 pub struct Rect {
     pub x: Interval,
     pub y: Interval,
@@ -36,11 +54,149 @@ impl Rect {
         }
     }
 
+    /// If the rect has no area at all.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty() || self.y.is_empty()
+    }
+
     #[must_use]
     pub fn contains(&self, subject: Point) -> bool {
         self.x.contains(subject.x) && self.y.contains(subject.y)
     }
 
+    /// If both rects share a part of an edge with a non-zero length,
+    /// so one could move from one rect into the other one.
+    /// Only touching at a corner does not count.
+    #[must_use]
+    pub fn is_adjacent(&self, other: &Self) -> bool {
+        (self.x.touches(&other.x) && self.y.overlaps(&other.y))
+            || (self.y.touches(&other.y) && self.x.overlaps(&other.x))
+    }
+
+    /// If both rects touch at exactly one corner, but share no edge,
+    /// so one can't move from one rect into the other one
+    /// even though they look connected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::{Interval, Rect};
+    /// let rect = |x, y| Rect {
+    ///     x: Interval::new(x, x + 100),
+    ///     y: Interval::new(y, y + 100),
+    /// };
+    /// assert!(rect(0, 0).touches_only_at_corner(&rect(100, 100)));
+    /// assert!(rect(0, 100).touches_only_at_corner(&rect(100, 0)));
+    /// // sharing an edge
+    /// assert!(!rect(0, 0).touches_only_at_corner(&rect(100, 50)));
+    /// // not touching at all
+    /// assert!(!rect(0, 0).touches_only_at_corner(&rect(101, 100)));
+    /// ```
+    #[must_use]
+    pub fn touches_only_at_corner(&self, other: &Self) -> bool {
+        self.x.touches(&other.x) && self.y.touches(&other.y)
+    }
+
+    /// If both rects share some area.
+    /// Only touching at an edge or corner does not count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::{Interval, Rect};
+    /// let a = Rect {
+    ///     x: Interval::new(0, 100),
+    ///     y: Interval::new(0, 100),
+    /// };
+    /// let b = Rect {
+    ///     x: Interval::new(50, 150),
+    ///     y: Interval::new(50, 150),
+    /// };
+    /// let c = Rect {
+    ///     x: Interval::new(100, 200),
+    ///     y: Interval::new(0, 100),
+    /// };
+    /// assert!(a.intersects(&b));
+    /// assert!(!a.intersects(&c));
+    /// ```
+    #[must_use]
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.x.overlaps(&other.x) && self.y.overlaps(&other.y)
+    }
+
+    /// The point in the middle of the rect,
+    /// rounded like [`Interval::mid`] for odd sizes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::{Interval, Point, Rect};
+    /// let rect = Rect {
+    ///     x: Interval::new(0, 1920),
+    ///     y: Interval::new(100, 1180),
+    /// };
+    /// assert_eq!(rect.center(), Point { x: 960, y: 640 });
+    /// ```
+    #[must_use]
+    pub fn center(&self) -> Point {
+        Point {
+            x: self.x.mid(),
+            y: self.y.mid(),
+        }
+    }
+
+    /// A copy of the rect with the same size,
+    /// moved so that its [`Rect::center`] is at `point`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::{Interval, Point, Rect};
+    /// let rect = Rect {
+    ///     x: Interval::new(0, 100),
+    ///     y: Interval::new(0, 50),
+    /// };
+    /// let centered = rect.centered_on(Point { x: 0, y: 0 });
+    /// assert_eq!(centered.x, Interval::new(-50, 50));
+    /// assert_eq!(centered.y, Interval::new(-25, 25));
+    /// assert_eq!(centered.size(), rect.size());
+    /// ```
+    #[must_use]
+    pub fn centered_on(&self, point: Point) -> Self {
+        let center = self.center();
+        *self
+            + Point {
+                x: point.x - center.x,
+                y: point.y - center.y,
+            }
+    }
+
+    /// A rect of the given `size` whose `corner` is at `point`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::{Corner, Hori, Interval, Point, Rect, Size, Vert};
+    /// let size = Size { width: 100, height: 50 };
+    /// let lower_left = Corner { hori: Hori::Left, vert: Vert::Bottom };
+    /// let rect = Rect::with_corner_at(lower_left, Point { x: 10, y: 0 }, size);
+    /// assert_eq!(rect.x, Interval::new(10, 110));
+    /// assert_eq!(rect.y, Interval::new(-50, 0));
+    /// ```
+    #[must_use]
+    pub fn with_corner_at(corner: Corner, point: Point, size: Size) -> Self {
+        let span = |at: Pixel, len: Pixel, side: Side| match side {
+            Side::Least => Interval::new(at, at + len),
+            Side::Most => Interval::new(at - len, at),
+        };
+
+        Self {
+            x: span(point.x, size.width, corner.hori.into()),
+            y: span(point.y, size.height, corner.vert.into()),
+        }
+    }
+
     /// If `target` is outside of the rect,
     /// move corners of the rect to exactly include it.
     /// Otherwise, do nothing.
@@ -134,13 +290,117 @@ impl Neg for Point {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// How far to move along one axis,
+/// either in logical pixels or physically.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub enum Length {
+    Pixels(Pixel),
+    Millimeters(f64),
+}
+
+impl Length {
+    /// This length in logical pixels,
+    /// given how many of them make up one millimeter on the screen.
+    /// [`None`] if that's needed, but unknown.
+    #[allow(clippy::cast_possible_truncation)] // screens are nowhere near that large
+    #[must_use]
+    pub fn to_pixels(self, per_mm: Option<f64>) -> Option<Pixel> {
+        match self {
+            Self::Pixels(px) => Some(px),
+            Self::Millimeters(mm) => Some((mm * per_mm?).round() as Pixel),
+        }
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pixels(px) => write!(f, "{px}"),
+            Self::Millimeters(mm) => write!(f, "{mm}mm"),
+        }
+    }
+}
+
+/// Like [`Point`], but each coordinate may be physical.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct Offset {
+    pub x: Length,
+    pub y: Length,
+}
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize, JsonSchema,
+)]
 pub struct Size {
     pub width: Pixel,
     pub height: Pixel,
 }
 
 impl Size {
+    /// Width divided by height.
+    /// Infinite or NaN if the height is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::Size;
+    /// let fhd = Size { width: 1920, height: 1080 };
+    /// assert!((fhd.aspect_ratio() - 16.0 / 9.0).abs() < f64::EPSILON);
+    /// ```
+    #[must_use]
+    pub fn aspect_ratio(&self) -> f64 {
+        self.width as f64 / self.height as f64
+    }
+
+    /// If the size is wider than it's high.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::{Rotation, Size};
+    /// let fhd = Size { width: 1920, height: 1080 };
+    /// assert!(fhd.is_landscape());
+    /// assert!(!fhd.rotate(Rotation::Quarter).is_landscape());
+    /// ```
+    #[must_use]
+    pub fn is_landscape(&self) -> bool {
+        self.width > self.height
+    }
+
+    /// Divides the size by `scale`,
+    /// rounding the result to whole pixels as specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::{Rounding, Size};
+    /// let wqhd = Size { width: 2560, height: 1440 };
+    /// // 2560 / 1.5 = 1706.66...
+    /// assert_eq!(wqhd.scaled_logical(1.5, Rounding::Floor).width, 1706);
+    /// assert_eq!(wqhd.scaled_logical(1.5, Rounding::Nearest).width, 1707);
+    /// assert_eq!(wqhd.scaled_logical(1.5, Rounding::Ceil).width, 1707);
+    ///
+    /// // 1440 / 1.75 = 822.85...
+    /// assert_eq!(wqhd.scaled_logical(1.75, Rounding::Floor).height, 822);
+    /// assert_eq!(wqhd.scaled_logical(1.75, Rounding::Nearest).height, 823);
+    /// assert_eq!(wqhd.scaled_logical(1.75, Rounding::Ceil).height, 823);
+    /// ```
+    #[allow(clippy::cast_possible_truncation)]
+    #[must_use]
+    pub fn scaled_logical(&self, scale: f64, rounding: Rounding) -> Self {
+        let scale = |len: Pixel| rounding.apply(len as f64 / scale) as Pixel;
+        Self {
+            width: scale(self.width),
+            height: scale(self.height),
+        }
+    }
+
     /// Flips width and height
     /// if the rotation is [`Rotation::Quarter`] or [`Rotation::ThreeQuarter`],
     #[must_use]
@@ -157,6 +417,24 @@ impl Size {
     }
 }
 
+/// Formats like `1920x1080`, which is also what [`str::parse`] accepts.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::geometry::Size;
+/// let size = Size { width: 1920, height: 1080 };
+/// assert_eq!(size.to_string(), "1920x1080");
+/// assert_eq!("1920x1080".parse::<Size>()?, size);
+/// assert!("3000000000x1080".parse::<Size>().is_err());
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
 impl Mul<f64> for Size {
     type Output = Self;
 
@@ -181,10 +459,72 @@ impl Div<f64> for Size {
     }
 }
 
+/// How to turn fractional pixels into whole ones.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Display,
+    ValueEnum,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Rounding {
+    /// To the nearest whole pixel, with halves rounded away from zero.
+    #[default]
+    Nearest,
+    /// Towards negative infinity.
+    Floor,
+    /// Towards positive infinity.
+    Ceil,
+}
+
+impl Rounding {
+    #[must_use]
+    pub fn apply(self, value: f64) -> f64 {
+        match self {
+            Self::Nearest => value.round(),
+            Self::Floor => value.floor(),
+            Self::Ceil => value.ceil(),
+        }
+    }
+}
+
+/// Physical dimensions of something, in millimeters.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize, JsonSchema,
+)]
+pub struct PhysicalSize {
+    pub width: u32,
+    pub height: u32,
+}
+
 /// Range thought in pixels.
 /// [`std::ops::RangeInclusive`] but not since it's too restricted
 /// and does not implement `PartialOrd`.
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+)]
 pub struct Interval {
     start: Pixel,
     end: Pixel,
@@ -230,6 +570,60 @@ impl Interval {
         self.start <= subject && subject <= self.end
     }
 
+    /// If one interval ends exactly where the other one starts.
+    #[must_use]
+    pub fn touches(&self, other: &Self) -> bool {
+        self.end == other.start || other.end == self.start
+    }
+
+    /// If both intervals share a part with a non-zero length.
+    /// Only touching does not count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::Interval;
+    /// let space = Interval::new(100, 200);
+    /// assert!(space.overlaps(&Interval::new(150, 250)));
+    /// assert!(space.overlaps(&Interval::new(120, 180)));
+    /// assert!(!space.overlaps(&Interval::new(200, 300)));
+    /// assert!(!space.overlaps(&Interval::new(0, 100)));
+    /// ```
+    #[must_use]
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start.max(other.start) < self.end.min(other.end)
+    }
+
+    /// This interval moved by as little as possible so it lies within `within`,
+    /// keeping its length.
+    /// If it's longer than `within`, it starts where `within` starts
+    /// and sticks out at the end.
+    ///
+    /// Not to be confused with [`Ord::clamp`],
+    /// which compares intervals as a whole.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::Interval;
+    /// let within = Interval::new(0, 1000);
+    /// assert_eq!(Interval::new(100, 200).clamp(within), Interval::new(100, 200));
+    /// assert_eq!(Interval::new(900, 1100).clamp(within), Interval::new(800, 1000));
+    /// assert_eq!(Interval::new(-300, -200).clamp(within), Interval::new(0, 100));
+    /// assert_eq!(Interval::new(500, 2000).clamp(within), Interval::new(0, 1500));
+    /// ```
+    #[must_use]
+    pub fn clamp(self, within: Interval) -> Interval {
+        let start = if self.len() > within.len() || self.start < within.start {
+            within.start
+        } else if self.end > within.end {
+            within.end - self.len()
+        } else {
+            self.start
+        };
+        Interval::new(start, start + self.len())
+    }
+
     /// Sets the length of this interval, keeping one limit
     /// and overriding the other one.
     pub fn set_len(&mut self, keep: Side, to: Pixel) {
@@ -296,12 +690,63 @@ impl Interval {
 
     /// Creates a new [`Interval`] of the given `length` inside of interval,
     /// on the given `side`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::{Interval, MaybeCenter, Side};
+    /// let space = Interval::new(100, 200);
+    /// assert_eq!(
+    ///     space.place_inside(20, MaybeCenter::Extreme(Side::Most)),
+    ///     Interval::new(180, 200),
+    /// );
+    /// // the point 30% along the new interval is 30% along the space
+    /// assert_eq!(
+    ///     space.place_inside(20, MaybeCenter::Along(0.3)),
+    ///     Interval::new(124, 144),
+    /// );
+    /// assert_eq!(
+    ///     space.place_inside(20, MaybeCenter::Along(1.0)),
+    ///     space.place_inside(20, MaybeCenter::Extreme(Side::Most)),
+    /// );
+    /// ```
+    #[allow(clippy::cast_possible_truncation)] // fractions are usually within 0 and 1
     #[must_use]
     pub fn place_inside(self, length: Pixel, pos: MaybeCenter<Side>) -> Self {
         match pos {
             MaybeCenter::Extreme(Side::Least) => Self::new(self.start(), self.start() + length),
             MaybeCenter::Center => Self::new(self.mid() - length / 2, self.mid() + length / 2),
             MaybeCenter::Extreme(Side::Most) => Self::new(self.end - length, self.end),
+            MaybeCenter::Along(fraction) => {
+                let start =
+                    self.start() + (f64::from(self.len() - length) * fraction).round() as Pixel;
+                Self::new(start, start + length)
+            }
+        }
+    }
+
+    /// The position at `pos` within this interval,
+    /// for [`MaybeCenter::Along`] that far from the start to the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::{Interval, MaybeCenter, Side};
+    /// let space = Interval::new(100, 200);
+    /// assert_eq!(space.point_at(MaybeCenter::Extreme(Side::Least)), 100);
+    /// assert_eq!(space.point_at(MaybeCenter::Center), 150);
+    /// assert_eq!(space.point_at(MaybeCenter::Along(0.3)), 130);
+    /// ```
+    #[allow(clippy::cast_possible_truncation)] // fractions are usually within 0 and 1
+    #[must_use]
+    pub fn point_at(self, pos: MaybeCenter<Side>) -> Pixel {
+        match pos {
+            MaybeCenter::Extreme(Side::Least) => self.start(),
+            MaybeCenter::Center => self.mid(),
+            MaybeCenter::Extreme(Side::Most) => self.end(),
+            MaybeCenter::Along(fraction) => {
+                self.start() + (f64::from(self.len()) * fraction).round() as Pixel
+            }
         }
     }
 
@@ -388,10 +833,14 @@ impl Default for VertSpec {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum MaybeCenter<T: Clone + Copy + fmt::Debug> {
     Extreme(T),
     Center,
+    /// Somewhere between both extremes,
+    /// 0 being the least and 1 the most one.
+    /// 0.5 is the same as [`Self::Center`].
+    Along(f64),
 }
 
 impl<T: Clone + Copy + fmt::Debug> MaybeCenter<T> {
@@ -399,6 +848,7 @@ impl<T: Clone + Copy + fmt::Debug> MaybeCenter<T> {
         match self {
             Self::Center => MaybeCenter::Center,
             Self::Extreme(extreme) => MaybeCenter::Extreme(op(extreme)),
+            Self::Along(fraction) => MaybeCenter::Along(fraction),
         }
     }
 }
@@ -436,13 +886,17 @@ impl From<Vert> for Side {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, JsonSchema,
+)]
 pub struct Transform {
     pub flipped: bool,
     pub rotation: Rotation,
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, JsonSchema,
+)]
 pub enum Rotation {
     #[default]
     None,
@@ -450,3 +904,157 @@ pub enum Rotation {
     Half,
     ThreeQuarter,
 }
+
+impl Transform {
+    /// The transform which undoes this one,
+    /// so that applying both after another in any order ends up at no transform at all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::Transform;
+    /// let sway = |raw| Transform::parse_from_sway(raw).unwrap();
+    /// assert_eq!(sway("90").inverse(), sway("270"));
+    /// // flipping is undone by flipping again, which also reverses the rotation
+    /// assert_eq!(sway("flipped-90").inverse(), sway("flipped-90"));
+    ///
+    /// for raw in ["normal", "180", "flipped", "flipped-270"] {
+    ///     assert_eq!(sway(raw).then(sway(raw).inverse()), Transform::default());
+    ///     assert_eq!(sway(raw).inverse().then(sway(raw)), Transform::default());
+    /// }
+    /// ```
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        if self.flipped {
+            // a mirror image mirrors the rotation as well
+            *self
+        } else {
+            Self {
+                flipped: false,
+                rotation: -self.rotation,
+            }
+        }
+    }
+
+    /// Applies first this transform, then `other`.
+    ///
+    /// Like Sway, a transform is thought of as flipping horizontally first
+    /// and rotating clockwise afterwards.
+    /// Since flipping reverses the direction of all rotations before it,
+    /// the order matters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::Transform;
+    /// let sway = |raw| Transform::parse_from_sway(raw).unwrap();
+    /// assert_eq!(sway("90").then(sway("180")), sway("270"));
+    /// assert_eq!(sway("flipped-90").then(sway("90")), sway("flipped-180"));
+    ///
+    /// // order matters
+    /// assert_eq!(sway("flipped").then(sway("90")), sway("flipped-90"));
+    /// assert_eq!(sway("90").then(sway("flipped")), sway("flipped-270"));
+    /// ```
+    #[must_use]
+    pub fn then(&self, other: Self) -> Self {
+        let rotation = if other.flipped {
+            other.rotation + -self.rotation
+        } else {
+            other.rotation + self.rotation
+        };
+
+        Self {
+            flipped: self.flipped != other.flipped,
+            rotation,
+        }
+    }
+}
+
+impl Rotation {
+    /// How many clockwise quarter turns this is.
+    #[must_use]
+    pub fn quarters(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Quarter => 1,
+            Self::Half => 2,
+            Self::ThreeQuarter => 3,
+        }
+    }
+
+    /// How many degrees this rotates clockwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::Rotation;
+    /// assert_eq!(Rotation::None.degrees(), 0);
+    /// assert_eq!(Rotation::ThreeQuarter.degrees(), 270);
+    /// ```
+    #[must_use]
+    pub fn degrees(self) -> u16 {
+        u16::from(self.quarters()) * 90
+    }
+
+    /// The rotation by `degrees` clockwise.
+    /// [`None`] if that's not one of 0, 90, 180 or 270.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::geometry::Rotation;
+    /// assert_eq!(Rotation::from_degrees(180), Some(Rotation::Half));
+    /// assert_eq!(Rotation::from_degrees(45), None);
+    /// assert_eq!(Rotation::from_degrees(360), None);
+    ///
+    /// for rotation in [Rotation::None, Rotation::Quarter, Rotation::Half, Rotation::ThreeQuarter] {
+    ///     assert_eq!(Rotation::from_degrees(rotation.degrees()), Some(rotation));
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_degrees(degrees: u16) -> Option<Self> {
+        match degrees {
+            0 => Some(Self::None),
+            90 => Some(Self::Quarter),
+            180 => Some(Self::Half),
+            270 => Some(Self::ThreeQuarter),
+            _ => None,
+        }
+    }
+
+    /// `quarters` clockwise quarter turns, full turns are left out.
+    #[must_use]
+    pub fn from_quarters(quarters: u8) -> Self {
+        match quarters % 4 {
+            0 => Self::None,
+            1 => Self::Quarter,
+            2 => Self::Half,
+            _ => Self::ThreeQuarter,
+        }
+    }
+}
+
+/// Rotates first by `self`, then by `rhs`.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::geometry::Rotation;
+/// assert_eq!(Rotation::Quarter + Rotation::Half, Rotation::ThreeQuarter);
+/// assert_eq!(Rotation::ThreeQuarter + Rotation::Half, Rotation::Quarter);
+/// assert_eq!(Rotation::Quarter + -Rotation::Quarter, Rotation::None);
+/// ```
+impl Add for Rotation {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::from_quarters(self.quarters() + rhs.quarters())
+    }
+}
+
+/// The same rotation, but counterclockwise.
+impl Neg for Rotation {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::from_quarters(4 - self.quarters())
+    }
+}
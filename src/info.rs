@@ -8,7 +8,7 @@
 //!
 //! Note 2: All the interesting information is after the macro definitions.
 
-use crate::geometry::Size;
+use crate::geometry::{Pixel, Size};
 
 use chumsky::prelude::*;
 use strum::{Display, EnumString};
@@ -44,6 +44,28 @@ macro_rules! connectors {
 
         impl Connector {
             make_chumsky_parser! { parse_from_name => $($( $dslrepr : $name ),*),* }
+
+            /// All names connectors can be referred to by in the DSL,
+            /// including aliases.
+            #[must_use] pub const fn dsl_names() -> &'static [&'static str] {
+                &[$($( $dslrepr ),*),*]
+            }
+
+            /// How this connector is called in the DSL,
+            /// without any of its aliases.
+            #[must_use] pub const fn dsl_name(&self) -> &'static str {
+                match self {
+                    $( Self::$name => [$( $dslrepr ),+][0], )*
+                }
+            }
+
+            /// The connector which is called exactly `name` in the DSL, if any.
+            #[must_use] pub fn from_dsl_name(name: &str) -> Option<Self> {
+                match name {
+                    $( $( $dslrepr )|+ => Some(Self::$name), )*
+                    _ => None,
+                }
+            }
         }
     }
 }
@@ -67,6 +89,61 @@ macro_rules! resolutions {
         }
 
         impl Resolution {
+            /// All resolutions which have a name, in the order of the table.
+            ///
+            /// # Examples
+            ///
+            /// No two of them have exactly the same size:
+            ///
+            /// ```
+            /// # use layaway::info::Resolution;
+            /// # use std::collections::BTreeSet;
+            /// let sizes: BTreeSet<_> = Resolution::all().iter().map(Resolution::size).collect();
+            /// assert_eq!(sizes.len(), Resolution::all().len());
+            /// ```
+            #[must_use] pub const fn all() -> &'static [Self] {
+                &[$( Self::$name, )*]
+            }
+
+            /// How this resolution is called in the DSL.
+            /// [`None`] for custom resolutions.
+            #[must_use] pub const fn dsl_name(&self) -> Option<&'static str> {
+                match self {
+                    $( Self::$name => Some($dslrepr), )*
+                    Self::Custom(_) => None,
+                }
+            }
+
+            /// All named resolutions which have exactly the given height.
+            #[must_use] pub fn with_height(height: Pixel) -> Vec<Self> {
+                Self::all()
+                    .iter()
+                    .copied()
+                    .filter(|res| res.size().height == height)
+                    .collect()
+            }
+
+            /// All named resolutions which have exactly the given size.
+            /// Empty if there's no name for it.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// # use layaway::{geometry::Size, info::Resolution};
+            /// let fhd = Size { width: 1920, height: 1080 };
+            /// assert_eq!(Resolution::aliases_for(fhd), [Resolution::Fhd]);
+            ///
+            /// let odd = Size { width: 1921, height: 1080 };
+            /// assert!(Resolution::aliases_for(odd).is_empty());
+            /// ```
+            #[must_use] pub fn aliases_for(size: Size) -> Vec<Self> {
+                Self::all()
+                    .iter()
+                    .copied()
+                    .filter(|res| res.size() == size)
+                    .collect()
+            }
+
             #[must_use] pub const fn size(&self) -> Size {
                 match self {
                     $( Self::$name => Size {
@@ -123,6 +200,94 @@ connectors! {
     "writeback" => "Writeback" @ Writeback,
     "spi" => "SPI" @ Spi,
     "usb" => "USB" @ Usb,
+
+    // not from libdrm, but how wlroots names outputs of its headless backend
+    "headless" => "HEADLESS" @ Headless,
+}
+
+impl Connector {
+    /// If the picture is sent as digital signal,
+    /// as opposed to an analog one.
+    /// Outputs which aren't backed by a cable at all count as digital.
+    /// [`Connector::Unknown`] is neither digital nor analog.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::info::Connector;
+    /// assert!(Connector::DisplayPort.is_digital());
+    /// assert!(Connector::Edp.is_digital());
+    /// assert!(!Connector::Vga.is_digital());
+    /// assert!(!Connector::Unknown.is_digital());
+    /// ```
+    #[must_use]
+    pub const fn is_digital(&self) -> bool {
+        !matches!(
+            self,
+            Self::Unknown
+                | Self::Vga
+                | Self::DviA
+                | Self::Composite
+                | Self::Svideo
+                | Self::Component
+                | Self::NinePinDin
+                | Self::Tv
+        )
+    }
+
+    /// If the screen is built into the device itself,
+    /// like the panel of a laptop or phone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::info::Connector;
+    /// assert!(Connector::Edp.is_internal());
+    /// assert!(Connector::Lvds.is_internal());
+    /// assert!(Connector::Dsi.is_internal());
+    /// assert!(!Connector::HdmiA.is_internal());
+    /// ```
+    #[must_use]
+    pub const fn is_internal(&self) -> bool {
+        matches!(self, Self::Edp | Self::Lvds | Self::Dsi)
+    }
+
+    /// The built-in name (or alias) which couldn't be used anymore
+    /// if `name` were a port alias,
+    /// since it's either the same or starts with `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::info::Connector;
+    /// assert_eq!(Connector::shadowed_by("dp"), Some("dp"));
+    /// assert_eq!(Connector::shadowed_by("hd"), Some("hdmib"));
+    /// assert_eq!(Connector::shadowed_by("main"), None);
+    /// ```
+    #[must_use]
+    pub fn shadowed_by(name: &str) -> Option<&'static str> {
+        Self::dsl_names()
+            .iter()
+            .copied()
+            .find(|builtin| builtin.starts_with(name))
+    }
+
+    /// If there's no physical screen at all behind the output,
+    /// e.g. since it's only used for streaming or screen capture.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::info::Connector;
+    /// assert!(Connector::Virtual.is_virtual());
+    /// assert!(Connector::Headless.is_virtual());
+    /// assert!(Connector::Writeback.is_virtual());
+    /// assert!(!Connector::DisplayPort.is_virtual());
+    /// ```
+    #[must_use]
+    pub const fn is_virtual(&self) -> bool {
+        matches!(self, Self::Virtual | Self::Headless | Self::Writeback)
+    }
 }
 
 resolutions! {
@@ -184,3 +349,25 @@ resolutions! {
     6144 x 3456 =>      "6k" @   Uhd6k,
     7680 x 4320 =>      "8k" @   Uhd8k,
 }
+
+impl Resolution {
+    /// The built-in name which couldn't be used anymore
+    /// if `name` were a custom resolution,
+    /// since it's either the same or starts with `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::info::Resolution;
+    /// assert_eq!(Resolution::shadowed_by("4k"), Some("4k"));
+    /// assert_eq!(Resolution::shadowed_by("dci"), Some("dci2k"));
+    /// assert_eq!(Resolution::shadowed_by("watch"), None);
+    /// ```
+    #[must_use]
+    pub fn shadowed_by(name: &str) -> Option<&'static str> {
+        Self::all()
+            .iter()
+            .filter_map(Self::dsl_name)
+            .find(|builtin| builtin.starts_with(name))
+    }
+}
@@ -20,22 +20,75 @@
 pub mod absolute;
 pub mod comms;
 pub mod config;
+pub mod control;
 pub mod convert;
 pub mod geometry;
 pub mod info;
+pub mod log;
 pub mod parse;
+#[cfg(feature = "pick")]
+pub mod pick;
+pub mod present;
 pub mod relative;
 
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process, thread,
+};
 
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, Parser, ValueEnum};
 use config::{Config, LayoutDesc};
 use eyre::{Context, ContextCompat, Result};
+use parse::dsl;
+use schemars::schema_for;
+use signal_hook::{
+    consts::{SIGHUP, SIGINT, SIGTERM},
+    iterator::Signals,
+};
+use thiserror::Error;
 
 pub type Map<K, V> = BTreeMap<K, V>;
 
+/// What can go wrong in [`plan`], [`desc_from_config`]
+/// or when talking to the WM,
+/// for matching on it instead of only reporting it.
+///
+/// [`run`] and friends use [`eyre`] instead,
+/// since they only ever report errors.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{comms::offline::Offline, convert::Options, parse::dsl::Names, Error};
+/// let planned = layaway::plan(
+///     "dp + edp/sideways",
+///     &Names::default(),
+///     &mut Offline::default(),
+///     &Options::default(),
+/// );
+/// assert!(matches!(planned, Err(Error::Parse(_))));
+/// ```
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Could not parse relative layout description: {0}")]
+    Parse(#[from] parse::dsl::ParseError),
+    #[error("Could not absolutize layout: {0}")]
+    Convert(#[from] convert::Error),
+    #[error(transparent)]
+    Config(#[from] config::Error),
+    #[error("Could not talk to WM: {0}")]
+    Comms(#[from] comms::Error),
+    #[error("Could not determine hostname to decide which layout to load: {0}")]
+    Hostname(io::Error),
+    #[error("Config file does not define layout for this machine, and no profile fits")]
+    NoLayout,
+}
+
 /// Calculates the physical screen layout given a short relative layout description.
 #[derive(Debug, Parser)]
+#[allow(clippy::struct_excessive_bools)] // they're all independent flags
 pub struct Args {
     #[allow(rustdoc::bare_urls)]
     /// Instead of using the machine-specific layout description from the config file,
@@ -46,7 +99,12 @@ pub struct Args {
     ///
     /// By default, the config file (`~/.config/layaway/config.toml` on Linux in most cases)
     /// is used to look up the layout description for the given hostname.
-    desc: Option<LayoutDesc>,
+    pub desc: Option<LayoutDesc>,
+
+    /// Use the config file at the given path
+    /// instead of the one in the default location.
+    #[arg(short, long, env = "LAYAWAY_CONFIG")]
+    pub config: Option<PathBuf>,
 
     /// Instead of applying the calculated layout,
     /// print the corresponding WM configuration to stdout.
@@ -54,41 +112,1031 @@ pub struct Args {
     /// By default, the calculated layout is directly applied to the WM,
     /// so that it becomes effective.
     #[arg(short = 'n', long = "no-apply", action = ArgAction::SetFalse)]
-    apply: bool,
+    pub apply: bool,
+
+    /// Instead of calculating a layout,
+    /// print a table of all outputs the WM currently knows about
+    /// and how they're configured.
+    #[arg(short, long, conflicts_with = "desc")]
+    pub list_outputs: bool,
+
+    /// Instead of calculating a layout,
+    /// print everything known about the given output,
+    /// including all modes it supports.
+    ///
+    /// Fails if the output isn't connected.
+    /// Combine with `--json` for a machine-readable form.
+    #[arg(short, long, value_name = "PORT", conflicts_with_all = ["desc", "list_outputs"])]
+    pub info: Option<comms::Port>,
+
+    /// Instead of calculating a layout,
+    /// arrange all outputs interactively using the arrow keys
+    /// and print the layout description for that arrangement.
+    #[cfg(feature = "pick")]
+    #[arg(long, conflicts_with_all = ["desc", "list_outputs", "info"])]
+    pub pick: bool,
+
+    /// When not applying, print the calculated layout as JSON
+    /// instead of WM configuration.
+    /// Short for `--format json`.
+    ///
+    /// The output can be applied later again using `--apply-json`.
+    #[arg(short, long, conflicts_with = "format")]
+    pub json: bool,
+
+    /// When not applying, how to print the calculated layout.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: Format,
+
+    /// Instead of calculating a layout,
+    /// apply the one stored as JSON in the given file as-is,
+    /// e.g. one previously printed by `--no-apply --json`.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["desc", "list_outputs"])]
+    pub apply_json: Option<PathBuf>,
+
+    /// If no WM is running and `--no-apply` is given,
+    /// pretend the outputs stored as JSON in the given file are connected,
+    /// e.g. as printed by `--no-apply --json` on the machine the layout is for.
+    ///
+    /// By default, no outputs are assumed to be connected then,
+    /// so only screens with a resolution in the layout description are placed.
+    #[arg(long, value_name = "FILE")]
+    pub offline_layout: Option<PathBuf>,
+
+    /// Instead of calculating a layout,
+    /// print the JSON Schema of the config file
+    /// or of the JSON printed by `--no-apply --json`.
+    ///
+    /// Editors can use it to validate and autocomplete them.
+    #[arg(long, value_name = "OF", value_enum, conflicts_with_all = ["desc", "list_outputs", "apply_json"])]
+    pub json_schema: Option<SchemaOf>,
+
+    /// Instead of calculating a layout,
+    /// print all names the layout description knows for connectors or resolutions,
+    /// one per line.
+    ///
+    /// Meant for shell completions.
+    #[arg(long, value_name = "OF", value_enum, hide = true, conflicts_with_all = ["desc", "list_outputs", "apply_json", "json_schema"])]
+    pub list: Option<ListOf>,
+
+    /// Instead of calculating a layout,
+    /// only turn on the given outputs, separated by commas,
+    /// leaving everything else about them as-is.
+    ///
+    /// Outputs are named like in the layout description, e.g. `dp2,edp`.
+    #[arg(
+        short,
+        long,
+        value_name = "PORTS",
+        value_delimiter = ',',
+        conflicts_with_all = ["desc", "list_outputs", "apply_json"],
+    )]
+    pub enable: Vec<comms::Port>,
+
+    /// Instead of calculating a layout,
+    /// turn the given output off if it's on, or on if it's off,
+    /// leaving everything else about it and all other outputs as-is.
+    #[arg(
+        short,
+        long,
+        value_name = "PORT",
+        conflicts_with_all = ["desc", "list_outputs", "apply_json", "enable"],
+    )]
+    pub toggle: Option<comms::Port>,
+
+    /// Instead of calculating a layout,
+    /// exchange where the two given outputs are, e.g. `dp1,dp2`,
+    /// leaving everything else about them and all other outputs as-is.
+    ///
+    /// Handy after plugging screens into each other's ports.
+    #[arg(
+        long,
+        value_name = "PORTS",
+        value_delimiter = ',',
+        num_args = 2,
+        conflicts_with_all = ["desc", "list_outputs", "apply_json", "enable", "toggle"],
+    )]
+    pub swap: Vec<comms::Port>,
+
+    /// Exchange the transforms of the outputs given to `--swap` as well.
+    #[arg(long, requires = "swap")]
+    pub swap_transforms: bool,
+
+    /// Instead of calculating a layout,
+    /// apply the profile after the one currently in effect
+    /// out of the given profiles from the config file, e.g. `docked,undocked`.
+    ///
+    /// After the last one, the first one follows again.
+    /// If none of them is in effect, the first one is applied.
+    #[arg(
+        long,
+        value_name = "PROFILES",
+        value_delimiter = ',',
+        conflicts_with_all = ["desc", "list_outputs", "apply_json", "enable", "toggle", "swap"],
+    )]
+    pub cycle: Vec<config::ProfileName>,
+
+    /// Calculate the whole layout, but only apply it to the given outputs,
+    /// separated by commas, leaving all others as-is.
+    ///
+    /// Outputs are named like in the layout description, e.g. `dp2,edp`.
+    /// The others are still placed, so positions relative to them work out.
+    #[arg(
+        long,
+        value_name = "PORTS",
+        value_delimiter = ',',
+        conflicts_with_all = [
+            "list_outputs",
+            "apply_json",
+            "enable",
+            "toggle",
+            "swap",
+            "cycle",
+            "listen",
+        ],
+    )]
+    pub only: Vec<comms::Port>,
+
+    /// Send all attributes of the calculated layout to the WM,
+    /// not only the ones specified explicitly.
+    ///
+    /// By default, attributes like the scale are only sent
+    /// if they're specified in the layout description,
+    /// so that settings made elsewhere are not overwritten.
+    ///
+    /// Can also be enabled in the config file via `emit_all` in `[defaults]`.
+    #[arg(long)]
+    pub emit_all: bool,
+
+    /// Save the layout description given on the CLI
+    /// as profile with the given name in the config file,
+    /// in addition to applying it.
+    ///
+    /// An existing profile with the same name is replaced.
+    #[arg(long, value_name = "NAME", requires = "desc")]
+    pub save_as: Option<String>,
+
+    /// How to round the size of scaled screens to whole pixels.
+    ///
+    /// Defaults to `rounding` in `[defaults]` of the config file,
+    /// or `nearest` if that isn't set either.
+    #[arg(long, value_enum)]
+    pub rounding: Option<geometry::Rounding>,
+
+    /// Keep the given screen at 0,0 and place all others around it,
+    /// even if they end up at negative coordinates.
+    ///
+    /// By default, the whole layout is moved so it starts at 0,0.
+    #[arg(long, value_name = "PORT")]
+    pub primary: Option<comms::Port>,
+
+    /// Keep the coordinates screens are placed at,
+    /// instead of moving the whole layout so it starts at 0,0.
+    ///
+    /// The first screen is at 0,0 then,
+    /// others placed left of or above it end up at negative coordinates.
+    #[arg(long = "no-reset", action = ArgAction::SetFalse)]
+    pub reset: bool,
+
+    /// Keep all screens within the region from 0,0 to the given size, like `4096x2160`,
+    /// e.g. the largest virtual screen a VM supports.
+    ///
+    /// Screens sticking out are moved in as little as possible,
+    /// screens larger than the region are left out with a warning.
+    #[arg(long, value_name = "SIZE")]
+    pub region: Option<geometry::Size>,
+
+    /// How far away from 0,0 screens may reach at most, in pixels.
+    /// Layouts reaching further are rejected,
+    /// since some applications and WMs break on large coordinates.
+    ///
+    /// Defaults to `max_coordinate` in `[defaults]` of the config file,
+    /// or 32767 if that isn't set either.
+    #[arg(long, value_name = "PIXELS")]
+    pub max_coordinate: Option<geometry::Pixel>,
+
+    /// Place the internal screen of a laptop (e.g. on eDP) first,
+    /// wherever it's listed,
+    /// and the first external screen without position centered above it.
+    ///
+    /// Can also be enabled in the config file via `around_internal` in `[defaults]`.
+    #[arg(long)]
+    pub around_internal: bool,
+
+    /// Start a new row below once the layout would become wider than this,
+    /// in pixels, instead of adding screens further to the right.
+    ///
+    /// Only screens without position of their own are wrapped.
+    ///
+    /// Can also be set in the config file via `wrap_width` in `[defaults]`.
+    #[arg(long, value_name = "PIXELS")]
+    pub wrap_width: Option<geometry::Pixel>,
+
+    /// Line up screens next to each other
+    /// at the `top`, `center` or `bottom` of the tallest one in their row,
+    /// after placing them.
+    ///
+    /// Only screens in the same row are affected.
+    #[arg(long, value_name = "SPEC")]
+    pub align_rows: Option<geometry::VertSpec>,
+
+    /// Use the given scale for all screens in the layout,
+    /// regardless of what the layout description says.
+    #[arg(long, value_name = "SCALE", value_parser = positive_scale)]
+    pub all_scale: Option<f64>,
+
+    /// Instead of applying one layout and exiting,
+    /// keep running and apply each layout description
+    /// sent to the control socket, e.g. via `--send`.
+    ///
+    /// `--no-apply` and `--emit-all` are honored for each of them.
+    #[arg(
+        long,
+        conflicts_with_all = ["desc", "list_outputs", "apply_json", "enable", "toggle", "swap"],
+    )]
+    pub listen: bool,
+
+    /// Instead of applying the given layout description directly,
+    /// send it to an instance started with `--listen`.
+    #[arg(long, requires = "desc", conflicts_with = "listen")]
+    pub send: bool,
+
+    /// Where the control socket for `--listen` and `--send` is.
+    ///
+    /// Defaults to `layaway.sock` in `$XDG_RUNTIME_DIR`.
+    #[arg(long, value_name = "PATH")]
+    pub socket: Option<PathBuf>,
+
+    /// Explain in plain English where each screen is placed and why,
+    /// in addition to applying the layout.
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Print the bounding box of all screens placed so far in their group
+    /// after placing each one, for debugging where screens end up.
+    ///
+    /// Also printed with `--verbose`.
+    #[arg(long)]
+    pub print_bounding_box: bool,
+
+    /// Instead of applying the layout,
+    /// check it against the connected outputs and the modes they support,
+    /// reporting all problems found at once.
+    ///
+    /// Fails if any of them would stop the layout from being applied.
+    #[arg(long)]
+    pub validate: bool,
+
+    /// Refuse to apply the layout
+    /// if any screen in it isn't connected,
+    /// unless it's marked as optional with `?`.
+    ///
+    /// Can also be enabled in the config file via `strict` in `[defaults]`.
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Don't print any warnings.
+    #[arg(short, long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Explain where each screen is placed
+    /// and which commands are sent to the WM.
+    #[arg(short, long)]
+    pub verbose: bool,
 }
 
+/// How `--no-apply` prints the calculated layout.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Sway commands, one per line,
+    /// see [`absolute::Layout::to_sway_directives`].
+    #[default]
+    Commands,
+    /// A block for the Sway config file,
+    /// see [`absolute::Layout::to_sway_config`].
+    SwayConfig,
+    /// The layout as JSON, which `--apply-json` can apply later again.
+    Json,
+}
+
+/// What `--json-schema` prints the schema of.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SchemaOf {
+    /// The config file, see [`Config`].
+    Config,
+    /// A calculated layout, see [`absolute::Layout`].
+    Layout,
+}
+
+impl SchemaOf {
+    /// The JSON Schema, pretty-printed.
+    pub fn schema(self) -> Result<String> {
+        let schema = match self {
+            Self::Config => schema_for!(Config),
+            Self::Layout => schema_for!(absolute::Layout),
+        };
+        Ok(serde_json::to_string_pretty(&schema)?)
+    }
+}
+
+/// What `--list` prints the names of.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ListOf {
+    /// All connector names including aliases, like `dp`.
+    Connectors,
+    /// All resolution names, followed by their size, like `1080p 1920x1080`.
+    Resolutions,
+}
+
+impl ListOf {
+    /// One name per line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::ListOf;
+    /// assert!(ListOf::Connectors.names().lines().any(|line| line == "edp"));
+    /// assert!(ListOf::Resolutions.names().lines().any(|line| line == "1080p 1920x1080"));
+    /// ```
+    #[must_use]
+    pub fn names(self) -> String {
+        let lines: Vec<_> = match self {
+            Self::Connectors => info::Connector::dsl_names()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            Self::Resolutions => info::Resolution::all()
+                .iter()
+                .filter_map(|res| Some(format!("{} {}", res.dsl_name()?, res.size())))
+                .collect(),
+        };
+        lines.into_iter().map(|line| line + "\n").collect()
+    }
+}
+
+impl Args {
+    /// How to print the layout when not applying it,
+    /// with `--json` taking precedence.
+    #[must_use]
+    pub fn format(&self) -> Format {
+        if self.json {
+            Format::Json
+        } else {
+            self.format
+        }
+    }
+}
+
+/// How to convert, from the CLI or otherwise the `[defaults]` of the config file.
+fn options(args: &Args, defaults: &config::Defaults) -> convert::Options {
+    convert::Options {
+        default_pos: defaults.position(),
+        rounding: args.rounding.or(defaults.rounding).unwrap_or_default(),
+        all_scale: args.all_scale,
+        origin: args.primary,
+        max_coordinate: args
+            .max_coordinate
+            .or(defaults.max_coordinate)
+            .unwrap_or(convert::Options::DEFAULT_MAX_COORDINATE),
+        around_internal: args.around_internal || defaults.around_internal,
+        wrap_width: args.wrap_width.or(defaults.wrap_width),
+        align_rows: args.align_rows,
+        strict: args.strict || defaults.strict,
+        reset_to_origin: args.reset,
+        region: args.region.map(|size| geometry::Rect {
+            x: geometry::Interval::new(0, size.width),
+            y: geometry::Interval::new(0, size.height),
+        }),
+    }
+}
+
+/// Parses the CLI arguments from the process and acts accordingly.
 pub fn run() -> Result<()> {
-    let args = Args::parse();
+    run_with(Args::parse())
+}
+
+/// Like [`run`], but with already specified arguments.
+/// Useful to drive layaway from another program
+/// without having `argv` looked at.
+pub fn run_with(args: Args) -> Result<()> {
+    log::set_level(if args.quiet {
+        log::Level::Quiet
+    } else if args.verbose {
+        log::Level::Verbose
+    } else {
+        log::Level::Normal
+    });
+
+    if let Some(of) = args.json_schema {
+        println!("{}", of.schema()?);
+        return Ok(());
+    }
+
+    if let Some(of) = args.list {
+        print!("{}", of.names());
+        return Ok(());
+    }
+
+    if args.send {
+        return send(&args);
+    }
+
+    let config = args
+        .config
+        .as_ref()
+        .map_or_else(Config::new, Config::from_path);
+    // needed for talking with the WM at all, so before anything else
+    let names = match &config {
+        Ok(config) => config
+            .custom_names()
+            .context("Could not load custom connectors")?,
+        Err(_) => comms::CustomNames::default(),
+    };
+
+    if args.list_outputs {
+        return list_outputs(&names);
+    }
+
+    if let Some(port) = args.info {
+        return info(port, &names, args.format() == Format::Json);
+    }
+
+    #[cfg(feature = "pick")]
+    if args.pick {
+        return pick(&names);
+    }
+
+    if let Some(path) = &args.apply_json {
+        return apply_json(path, &names, args.apply, args.format());
+    }
+
+    if !args.enable.is_empty() {
+        return enable(&args.enable, &names, args.apply, args.format());
+    }
+
+    if let Some(port) = args.toggle {
+        return toggle(port, &names, args.apply, args.format());
+    }
+
+    if !args.swap.is_empty() {
+        return swap(
+            &args.swap,
+            args.swap_transforms,
+            &names,
+            args.apply,
+            args.format(),
+        );
+    }
+
+    let config = config_or_default(config, &args)?;
+    let dsl_names = config
+        .names()
+        .context("Could not load custom resolutions and aliases")?;
+
+    let opts = options(&args, &config.defaults);
+    let mut comms = connect(&args, &names)?;
+
+    if !args.cycle.is_empty() {
+        return cycle(
+            &args.cycle,
+            &config,
+            comms.as_mut(),
+            &opts,
+            &names,
+            args.apply,
+            args.format(),
+        );
+    }
+
+    let emit_all = args.emit_all || config.defaults.emit_all;
+    if args.listen {
+        let path = socket_path(&args)?;
+        remove_on_exit(&path)?;
+        let (apply, format) = (args.apply, args.format());
+        control::serve(
+            &path,
+            comms.as_mut(),
+            &opts,
+            &dsl_names,
+            &mut |mut layout, comms| {
+                if emit_all {
+                    send_all_attributes(&mut layout);
+                }
+                emit(&layout, comms, &names, apply, format)
+            },
+        )?;
+        return Ok(());
+    }
 
-    let desc = args.desc.map_or_else(desc_from_config, Ok)?;
+    let (desc, profile) = if let Some(desc) = args.desc {
+        (desc, None)
+    } else {
+        let current = comms
+            .layout()
+            .context("Could not fetch current layout from WM")?;
+        desc_from_config(&config, &current)?
+    };
+    if args.validate {
+        return validate(&desc, &dsl_names, comms.as_mut(), &opts);
+    }
+
+    let convert::Converted {
+        mut layout,
+        warnings,
+        bounding_boxes,
+    } = plan(&desc, &dsl_names, comms.as_mut(), &opts)?;
+    if args.print_bounding_box || log::enabled(log::Level::Verbose) {
+        for (port, bb) in bounding_boxes {
+            eprintln!(
+                "after {port}: bb = ({}..{}, {}..{})",
+                bb.x.start(),
+                bb.x.end(),
+                bb.y.start(),
+                bb.y.end(),
+            );
+        }
+    }
+    // before filtering, which warns about what it leaves out on its own,
+    // so that those aren't explained as not connected
+    if args.explain {
+        explain(&desc, &dsl_names, &layout, &opts)?;
+    }
+    if let Some(profile) = profile {
+        restrict(&mut layout, profile, &dsl_names)?;
+    }
+    if !args.only.is_empty() {
+        keep_only(&mut layout, &args.only);
+    }
+    for warning in &warnings {
+        log::warning!("{warning}");
+    }
+
+    // only now, so that broken layout descriptions aren't saved
+    if let Some(name) = &args.save_as {
+        save_as(name, &desc, args.config.as_deref())?;
+    }
+
+    if emit_all {
+        send_all_attributes(&mut layout);
+    }
+
+    emit(&layout, comms.as_mut(), &names, args.apply, args.format())
+}
+
+/// The loaded `config`, falling back to the default one where that's fine.
+fn config_or_default(config: Result<Config, config::Error>, args: &Args) -> Result<Config> {
+    match config {
+        Ok(config) => Ok(config),
+        // the layout description is given directly,
+        // so the config would only be needed for defaults anyway
+        Err(config::Error::Load { .. }) if args.desc.is_some() || args.listen => {
+            Ok(Config::default())
+        }
+        Err(err) => Err(err).context("Could not load config"),
+    }
+}
+
+/// Prints a table of all outputs the WM currently knows about.
+fn list_outputs(names: &comms::CustomNames) -> Result<()> {
+    let mut comms = comms::establish(names).context("Could not establish connection to WM")?;
+    let current = comms
+        .layout()
+        .context("Could not fetch current layout from WM")?;
+    print!("{}", present::table(&current));
+    Ok(())
+}
+
+/// Stores `desc` as profile called `name` in the config file,
+/// either at `path` or the default location.
+fn save_as(name: &str, desc: &str, path: Option<&Path>) -> Result<()> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => Config::default_path().context("Could not determine config path")?,
+    };
+    let profile = config::Profile {
+        layout: desc.to_string(),
+        when: config::Conditions::default(),
+        allow: None,
+        deny: Vec::new(),
+    };
+    Config::save_profile(&path, name, &profile).context("Could not save profile")
+}
+
+/// Reports everything wrong with `desc`, see [`Args::validate`].
+fn validate(
+    desc: &str,
+    dsl_names: &dsl::Names,
+    comms: &mut dyn comms::Comms,
+    opts: &convert::Options,
+) -> Result<()> {
+    let relative = relative::Layout::parse_with(desc, dsl_names).map_err(Error::from)?;
+    let ctx = convert::Context::fetch(comms).map_err(Error::from)?;
+    let convert::Validation { errors, warnings } = ctx.validate(&relative, opts);
+
+    for warning in &warnings {
+        log::warning!("{warning}");
+    }
+    for err in &errors {
+        eprintln!("Error: {err}");
+    }
+    if !errors.is_empty() {
+        eyre::bail!("Layout description has {} problem(s)", errors.len());
+    }
+
+    log::verbose!("Layout description is fine");
+    Ok(())
+}
+
+/// Prints everything known about the output at `port`,
+/// as JSON if `json` is set.
+fn info(port: comms::Port, names: &comms::CustomNames, json: bool) -> Result<()> {
+    let mut comms = comms::establish(names).context("Could not establish connection to WM")?;
+    let current = comms
+        .layout()
+        .context("Could not fetch current layout from WM")?;
+    let Some(cfg) = current.outputs.get(&port) else {
+        let connected: Vec<_> = current.outputs.keys().map(ToString::to_string).collect();
+        eyre::bail!(
+            "{port} is not connected, connected are: {}",
+            connected.join(", "),
+        );
+    };
+    let modes = comms
+        .modes()
+        .context("Could not fetch modes from WM")?
+        .remove(&port)
+        .unwrap_or_default();
+
+    if json {
+        let info = serde_json::json!({ "port": port, "config": cfg, "modes": modes });
+        let info = serde_json::to_string_pretty(&info).context("Could not serialize output")?;
+        println!("{info}");
+    } else {
+        let output = absolute::OutputRef { port: &port, cfg };
+        print!("{}", present::info(output, &modes));
+    }
+
+    Ok(())
+}
+
+/// Lets the user arrange all outputs interactively
+/// and prints the resulting layout description,
+/// unless they aborted.
+#[cfg(feature = "pick")]
+fn pick(names: &comms::CustomNames) -> Result<()> {
+    let mut comms = comms::establish(names).context("Could not establish connection to WM")?;
+    let current = comms
+        .layout()
+        .context("Could not fetch current layout from WM")?;
+    if let Some(desc) = pick::pick(&current).context("Could not pick layout")? {
+        println!("{desc}");
+    }
+
+    Ok(())
+}
+
+/// Applies the layout stored as JSON at `path` as-is,
+/// see [`emit`] for `names`, `apply` and `format`.
+fn apply_json(path: &Path, names: &comms::CustomNames, apply: bool, format: Format) -> Result<()> {
+    let layout = read_layout(path)?;
+    let mut comms = comms::establish(names).context("Could not establish connection to WM")?;
+    let current = comms
+        .layout()
+        .context("Could not fetch current layout from WM")?;
+    for &port in layout.outputs.keys() {
+        if !current.outputs.contains_key(&port) {
+            log::warning!("{}", convert::Warning::Disconnected { port });
+        }
+    }
+
+    emit(&layout, comms.as_mut(), names, apply, format)
+}
+
+/// Turns on all `ports` without changing anything else about them,
+/// see [`emit`] for `names`, `apply` and `format`.
+fn enable(
+    ports: &[comms::Port],
+    names: &comms::CustomNames,
+    apply: bool,
+    format: Format,
+) -> Result<()> {
+    let layout = ports
+        .iter()
+        .map(|&port| absolute::Output {
+            port,
+            cfg: absolute::OutputConfig {
+                active: true,
+                ..Default::default()
+            },
+        })
+        .collect();
+
+    let mut comms = comms::establish(names).context("Could not establish connection to WM")?;
+    emit(&layout, comms.as_mut(), names, apply, format)
+}
+
+/// Turns `port` off if it's on, or on if it's off,
+/// see [`emit`] for `names`, `apply` and `format`.
+fn toggle(
+    port: comms::Port,
+    names: &comms::CustomNames,
+    apply: bool,
+    format: Format,
+) -> Result<()> {
+    let mut comms = comms::establish(names).context("Could not establish connection to WM")?;
+    let current = comms
+        .layout()
+        .context("Could not fetch current layout from WM")?;
+    let layout = current
+        .toggled(port)
+        .with_context(|| format!("{port} is not connected, so it can't be toggled"))?;
+
+    emit(&layout, comms.as_mut(), names, apply, format)
+}
+
+/// Exchanges where the two `ports` are, and their transforms too if `transforms`,
+/// see [`emit`] for `names`, `apply` and `format`.
+fn swap(
+    ports: &[comms::Port],
+    transforms: bool,
+    names: &comms::CustomNames,
+    apply: bool,
+    format: Format,
+) -> Result<()> {
+    let [a, b] = ports[..] else {
+        eyre::bail!("Can only swap exactly two outputs, not {}", ports.len());
+    };
 
-    let relative: relative::Layout = desc
-        .parse()
-        .context("Could not parse relative layout description")?;
+    let mut comms = comms::establish(names).context("Could not establish connection to WM")?;
+    let current = comms
+        .layout()
+        .context("Could not fetch current layout from WM")?;
+    for port in [a, b] {
+        match current.outputs.get(&port) {
+            None => eyre::bail!("{port} is not connected, so it can't be swapped"),
+            Some(cfg) if !cfg.active => eyre::bail!("{port} is off, so it can't be swapped"),
+            Some(_) => {}
+        }
+    }
+    let layout = current
+        .swapped(a, b, transforms)
+        .expect("both outputs were just checked to be connected and active");
 
-    let mut comms = comms::establish().context("Could not establish connection to WM")?;
-    let layout = relative
-        .to_absolute(comms.as_mut())
-        .context("Could not absolutize layout")?;
+    emit(&layout, comms.as_mut(), names, apply, format)
+}
 
-    if args.apply {
+/// Applies the layout to the WM,
+/// or prints it in the given `format` if not to `apply`,
+/// calling outputs like in `names`.
+fn emit(
+    layout: &absolute::Layout,
+    comms: &mut dyn comms::Comms,
+    names: &comms::CustomNames,
+    apply: bool,
+    format: Format,
+) -> Result<()> {
+    if apply {
+        for cmd in layout.to_sway_commands(names) {
+            log::verbose!("Sending: {cmd}");
+        }
         comms
-            .set_layout(&layout)
+            .set_layout_atomic(layout)
             .context("Could not set layout in WM")?;
     } else {
-        for cmd in layout.to_sway_commands() {
-            println!("{cmd}");
+        match format {
+            Format::Commands => {
+                for line in layout.to_sway_directives(names) {
+                    println!("{line}");
+                }
+            }
+            Format::SwayConfig => print!("{}", layout.to_sway_config(names)),
+            Format::Json => {
+                let json =
+                    serde_json::to_string_pretty(layout).context("Could not serialize layout")?;
+                println!("{json}");
+            }
         }
     }
 
     Ok(())
 }
 
-pub fn desc_from_config() -> Result<LayoutDesc> {
-    let config = Config::new()?;
+/// Parses a scale given on the CLI,
+/// which has to be greater than 0 just like in the layout description.
+fn positive_scale(raw: &str) -> Result<f64, String> {
+    let scale: f64 = raw.parse().map_err(|err| format!("{err}"))?;
+    if scale > 0.0 {
+        Ok(scale)
+    } else {
+        Err("scale has to be greater than 0".to_string())
+    }
+}
+
+/// Marks all attributes of `layout` as explicit, see [`Args::emit_all`].
+fn send_all_attributes(layout: &mut absolute::Layout) {
+    for cfg in layout.outputs.values_mut() {
+        cfg.explicit = absolute::Explicit::ALL;
+    }
+}
+
+/// Reads a layout stored as JSON, like printed by `--no-apply --json`.
+fn read_layout(path: &Path) -> Result<absolute::Layout> {
+    let source = fs::read_to_string(path)
+        .with_context(|| format!("Could not read layout from `{}`", path.display()))?;
+    serde_json::from_str(&source).context("Could not parse layout JSON")
+}
+
+/// Establishes a connection to the WM.
+/// If there's none but nothing needs to be applied anyway,
+/// falls back to [`comms::offline::Offline`].
+fn connect(args: &Args, names: &comms::CustomNames) -> Result<Box<dyn comms::Comms>> {
+    match comms::establish(names) {
+        Err(comms::Error::NoWmRunning) if !args.apply && !args.listen => {
+            let layout = if let Some(path) = &args.offline_layout {
+                read_layout(path)?
+            } else {
+                log::warning!(
+                    "No WM is running, so screens without a resolution in the layout description will be skipped"
+                );
+                absolute::Layout::new()
+            };
+            Ok(Box::new(comms::offline::Offline::new(layout)))
+        }
+        comms => comms.context("Could not establish connection to WM"),
+    }
+}
+
+/// The control socket given on the CLI, or the default one.
+fn socket_path(args: &Args) -> Result<PathBuf> {
+    match &args.socket {
+        Some(path) => Ok(path.clone()),
+        None => Ok(control::default_path()?),
+    }
+}
+
+/// Removes the control socket at `path` once interrupted or terminated,
+/// so it isn't left over.
+fn remove_on_exit(path: &Path) -> Result<()> {
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP])
+        .context("Could not set up removing the control socket on exit")?;
+    let path = path.to_path_buf();
+    thread::spawn(move || {
+        if let Some(signal) = signals.forever().next() {
+            // might not even be bound yet, which is fine
+            let _ = fs::remove_file(&path);
+            process::exit(128 + signal);
+        }
+    });
+    Ok(())
+}
+
+/// Hands the layout description to the instance listening on the control socket.
+fn send(args: &Args) -> Result<()> {
+    let desc = args
+        .desc
+        .as_ref()
+        .context("No layout description given to send")?;
+    let warnings = control::send(&socket_path(args)?, desc)?;
+    for warning in warnings.lines() {
+        log::warning!("{warning}");
+    }
+    Ok(())
+}
+
+/// Prints how each screen of `desc` ended up where it is in `placed`.
+fn explain(
+    desc: &str,
+    dsl_names: &dsl::Names,
+    placed: &absolute::Layout,
+    opts: &convert::Options,
+) -> Result<()> {
+    let relative = relative::Layout::parse_with(desc, dsl_names)?;
+    let default_pos = relative
+        .flow
+        .map_or(opts.default_pos, relative::Flow::default_pos);
+    eprint!("{}", present::explain(&relative, placed, default_pos));
+    Ok(())
+}
+
+/// Calculates the absolute layout for the given layout description,
+/// which may use `names` in addition to the built-in ones,
+/// without applying it.
+/// `comms` is only used to look up the current layout.
+pub fn plan(
+    desc: &str,
+    names: &dsl::Names,
+    comms: &mut dyn comms::Comms,
+    opts: &convert::Options,
+) -> Result<convert::Converted, Error> {
+    let relative = relative::Layout::parse_with(desc, names)?;
+    let converted = relative.to_absolute_with(comms, opts)?;
+    Ok(converted)
+}
+
+/// Looks up which layout description to use,
+/// preferring the [best profile] for the `current` layout
+/// over the layout for this machine.
+/// The profile is returned as well if one is used.
+///
+/// [best profile]: Config::best_profile
+pub fn desc_from_config<'a>(
+    config: &'a Config,
+    current: &absolute::Layout,
+) -> Result<(LayoutDesc, Option<&'a config::Profile>), Error> {
+    if let Some((name, profile)) = config.best_profile(current, &config.names()?) {
+        log::verbose!("Using profile `{name}`");
+        return Ok((profile.layout.clone(), Some(profile)));
+    }
+
     let desc = config
         .machine_layout()
-        .context("Could not determine hostname to decide which layout to load")?
-        .context("Config file does not define layout for this machine")?;
-    Ok(desc.to_string())
+        .map_err(Error::Hostname)?
+        .ok_or(Error::NoLayout)?;
+    Ok((desc.clone(), None))
+}
+
+/// Applies the profile after the first one of `profiles` which is in effect,
+/// see [`Args::cycle`] and [`emit`] for `names`, `apply` and `json`.
+fn cycle(
+    profiles: &[config::ProfileName],
+    config: &Config,
+    comms: &mut dyn comms::Comms,
+    opts: &convert::Options,
+    names: &comms::CustomNames,
+    apply: bool,
+    format: Format,
+) -> Result<()> {
+    let current = comms
+        .layout()
+        .context("Could not fetch current layout from WM")?;
+    let dsl_names = config
+        .names()
+        .context("Could not load custom resolutions and aliases")?;
+
+    let mut planned = Vec::with_capacity(profiles.len());
+    for name in profiles {
+        let profile = config
+            .profiles
+            .get(name)
+            .with_context(|| format!("Config file has no profile called `{name}`"))?;
+        let convert::Converted {
+            mut layout,
+            warnings,
+            ..
+        } = plan(&profile.layout, &dsl_names, comms, opts)
+            .with_context(|| format!("Could not calculate profile `{name}`"))?;
+        restrict(&mut layout, profile, &dsl_names)?;
+        planned.push((name, layout, warnings));
+    }
+
+    let next = planned
+        .iter()
+        .position(|(_, layout, _)| layout.is_in_effect(&current))
+        .map_or(0, |in_effect| (in_effect + 1) % planned.len());
+    let (name, layout, warnings) = &planned[next];
+
+    log::verbose!("Cycling to profile `{name}`");
+    for warning in warnings {
+        log::warning!("{warning}");
+    }
+    emit(layout, comms, names, apply, format)
+}
+
+/// Leaves out all outputs of `layout` which `profile` may not change,
+/// so they're left as-is in the WM.
+/// Its outputs are named like in `names`.
+fn restrict(
+    layout: &mut absolute::Layout,
+    profile: &config::Profile,
+    names: &dsl::Names,
+) -> Result<()> {
+    let mut denied = Vec::new();
+    for &port in layout.outputs.keys() {
+        if !profile.permits(port, names)? {
+            denied.push(port);
+        }
+    }
+
+    for port in denied {
+        log::warning!("{port} is in the layout, but the profile may not change it, skipping it");
+        layout.outputs.remove(&port);
+        layout.complete = false;
+    }
+    Ok(())
+}
+
+/// Leaves out all outputs of `layout` which aren't in `ports`,
+/// see [`Args::only`].
+fn keep_only(layout: &mut absolute::Layout, ports: &[comms::Port]) {
+    for port in ports {
+        if !layout.outputs.contains_key(port) {
+            log::warning!("{port} should be applied, but it isn't in the layout");
+        }
+    }
+
+    let before = layout.outputs.len();
+    layout.outputs.retain(|port, _| ports.contains(port));
+    if layout.outputs.len() != before {
+        layout.complete = false;
+    }
 }
@@ -0,0 +1,53 @@
+//! Minimal leveled diagnostics on stderr.
+//!
+//! Use [`warning!`] for things the user should probably act on
+//! and [`verbose!`] for explaining what's going on in detail.
+//! Which of them are actually printed is decided by [`set_level`].
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much to print.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Level {
+    /// Nothing at all.
+    Quiet,
+    /// Only warnings.
+    #[default]
+    Normal,
+    /// Warnings and details about what's being calculated and sent.
+    Verbose,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Normal as u8);
+
+/// Sets what to print from now on, for the whole process.
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// If messages of the given `level` are to be printed.
+#[must_use]
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+/// Prints a warning to stderr, unless quiet.
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Level::Normal) {
+            eprintln!("Warning: {}", format_args!($($arg)*));
+        }
+    };
+}
+
+/// Prints details to stderr, but only if verbose.
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Level::Verbose) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use {verbose, warning};
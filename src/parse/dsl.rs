@@ -16,6 +16,10 @@
 //! vga3 + dp + edp
 //! ```
 //!
+//! For those used to other tools,
+//! `,` and `&` work as well instead of `+`,
+//! so `vga3, dp, edp` is the same layout.
+//!
 //! This would place the screens on
 //!     VGA port 3,
 //!     `DisplayPort` 1 and
@@ -83,38 +87,271 @@
 //!     so that the **upper right** corner of _B_
 //!     touches the upper left corner of _A_.
 //!
+//! For anything in between, the second part can also be a number from 0 to 1,
+//! saying how far along the edge _B_ is placed,
+//! like `right,0.3`.
+//! The point that far along _B_'s edge then touches
+//! the point that far along _A_'s edge,
+//! so `0` is the same as `top` or `left`,
+//! `0.5` the same as `center`
+//! and `1` the same as `bottom` or `right`.
+//!
+//! Finally, after another comma, the corner of _B_
+//! which touches that point on the edge of _A_ can be chosen explicitly,
+//! as one of `upper-left`, `upper-right`, `lower-left` or `lower-right`.
+//! For example, `right,top,lower-left` places _B_
+//!     so that its **lower left** corner
+//!     touches the upper right corner of _A_,
+//!     so they only touch diagonally.
+//! Without it, the corner is chosen as described above,
+//! so `right,top` is the same as `right,top,upper-left`.
+//! With `center` or a number, the point is that far along the edge of _A_.
+//!
+//! ## Anchors
+//!
+//! Instead of the bounding box of all screens until now,
+//! a screen can also be placed next to one specific screen before it.
+//! For that, the position is one of
+//! `above`, `below`, `left-of` or `right-of`
+//! followed by the screen to place it next to, called the anchor:
+//!
+//! ```text
+//! dp + hdmi + edp/below dp
+//! ```
+//!
+//! This places the embedded `DisplayPort` centered below the `DisplayPort` one,
+//! no matter how wide the HDMI one is.
+//! `above` and `below` are the same as `top` and `bottom` respectively,
+//! `left-of` and `right-of` the same as `left` and `right`,
+//! just with _A_ being the anchor.
+//! So they can be followed by the exact placement as well,
+//! like `left-of dp,bottom`.
+//!
+//! ## Offsets
+//!
+//! To nudge a screen a bit from where its position puts it,
+//! `+` followed by how far to move it to the right and down
+//! can follow the position:
+//!
+//! ```text
+//! dp + edp/bottom,right +10,-5
+//! ```
+//!
+//! This places the embedded `DisplayPort` at the bottom right,
+//! then moves it 10 pixels to the right and 5 pixels up.
+//! Offsets are applied as-is,
+//! so if they make screens overlap, they will,
+//! with a warning.
+//!
+//! The offset is always applied last,
+//! after the position placed the screen,
+//! including how far along the edge it is.
+//! So `right,0.3 +0,-20` first places the screen 30% of the way down
+//! the right edge, then moves it 20 pixels up from there.
+//!
+//! Offsets can also be physical lengths in `mm`, `cm` or `in`,
+//! e.g. for a projector whose picture should start a bit further right:
+//!
+//! ```text
+//! dp + hdmi/right +30cm,0
+//! ```
+//!
+//! They're converted into pixels using how large the moved screen itself
+//! physically is according to the WM, which fails if that is unknown.
+//! This is only an approximation,
+//! since screens report their size in whole millimeters at best,
+//! and for projectors it depends on the distance to the wall anyway.
+//!
+//! ## Exact positions
+//!
+//! If a screen has to be at one exact spot,
+//! `@@` followed by its upper left corner places it there
+//! instead of relative to the others:
+//!
+//! ```text
+//! dp + edp@@0,1440
+//! ```
+//!
+//! Screens with an exact position are placed first,
+//! no matter where they are in the group,
+//! so all other screens are placed around them.
+//! Unlike otherwise, the layout as a whole is not moved to start at 0,0 afterwards,
+//! and its group is not moved next to earlier groups,
+//! so the position is exactly the one given.
+//!
+//! ## Flow
+//!
+//! If most screens are placed below each other anyway,
+//! specifying `bottom` for each of them gets tedious quickly.
+//! Instead, the layout can be prefixed with `vertical:`,
+//! which changes the default position to `bottom,center`:
+//!
+//! ```text
+//! vertical: dp + edp + hdmi
+//! ```
+//!
+//! The opposite, `horizontal:`, is what's assumed by default.
+//!
+//! To stack screens below each other
+//! with all of them aligned at the same side,
+//! like portrait screens for reading code,
+//! use `stack` followed by `left`, `center` or `right`:
+//!
+//! ```text
+//! stack left: dp1 + dp2 + dp3
+//! ```
+//!
+//! Unlike `vertical:`, this also aligns screens with their own position,
+//! so `stack left: dp1 + dp2/bottom,center` still puts `dp2` on the left.
+//! Without a side, `stack:` aligns the centers.
+//!
+//! ## Groups
+//!
+//! Sometimes screens have nothing to do with each other,
+//! for example if they're used by different people.
+//! In that case, they can be split into groups using `;`:
+//!
+//! ```text
+//! dp + edp/bottom; hdmi + vga/left
+//! ```
+//!
+//! Each group is laid out on its own,
+//! as if the other groups didn't exist.
+//! Afterwards, the groups are placed from left to right,
+//! with their upper edges aligned,
+//! so they don't overlap.
+//!
+//! ## Shorthands
+//!
+//! The probably most common setup,
+//! a laptop placed centered below an external screen,
+//! can be written as `laptop-below` followed by the external screen:
+//!
+//! ```text
+//! laptop-below dp2@4k
+//! ```
+//!
+//! This is the same as `dp2@4k + edp/bottom,center`.
+//!
+//! ## Shared attributes
+//!
+//! Screens which should all have e.g. the same scale and transform,
+//! like the panels of a video wall,
+//! can be put in braces.
+//! Everything after the closing brace
+//! then applies to all of them:
+//!
+//! ```text
+//! {dp1 + dp2 + dp3/bottom,left + dp4}:1.5#90
+//! ```
+//!
+//! Inside the braces, the screens are laid out like everywhere else.
+//! If a screen specifies an attribute itself,
+//! that one is used instead of the shared one.
+//! A shared position only applies to the first screen in the braces,
+//! since that's where the others continue from.
+//!
+//! ## Everything else
+//!
+//! If most screens should be configured the same,
+//! `*` stands for all connected screens
+//! which aren't mentioned anywhere else in the layout:
+//!
+//! ```text
+//! *@4k:2 + edp/bottom
+//! ```
+//!
+//! They're placed where the `*` is, ordered by port,
+//! one after another like screens without position.
+//! Attributes after the `*` apply to each of them,
+//! but a position only to the first one.
+//! `*` can only appear once in the whole layout.
+//!
+//! ## Grids
+//!
+//! Screens can also be arranged in columns and rows,
+//! filling up each row before the next one:
+//!
+//! ```text
+//! grid 2x2 [dp1 dp2 dp3 dp4]:1.5
+//! ```
+//!
+//! Each screen is right of the one before in its row with tops aligned,
+//! each row is below the first screen of the row before with lefts aligned.
+//! The number of screens has to match the grid.
+//!
 //! # [ABNF]
 //!
 //! ```ebnf
-//! layout = screen *(sp "+" sp screen)
-//! screen =           port
-//!         [sp "@" sp resolution]
-//!         [sp ":" sp scale]
+//! layout = [flow sp ":" sp] group *(sp ";" sp group)
+//! flow = "horizontal" / "vertical" / "stack" [1*WSP hori-spec]
+//! group = members *(sp sep sp members)
+//! sep = "+" / "," / "&"
+//! cell-sep = sp sep sp / 1*WSP
+//! members = screen
+//!         / "*" attrs
+//!         / "laptop-below" 1*WSP screen
+//!         / "grid" 1*WSP integer "x" integer sp "[" sp screen *(cell-sep screen) sp "]" attrs
+//!         / "{" sp members *(sp sep sp members) sp "}" attrs
+//! screen = port ["?"] attrs
+//! attrs = [sp "@" sp mode]
+//!         [sp "*" sp (refresh-range / refresh)]
+//!         [sp ":" sp (scale / "like" sp port)]
 //!         [sp "#" sp transform]
-//!         [sp "/" sp pos]
+//!         *(sp "%" sp option)
+//!         [sp "/" sp (pos / anchored-pos) [sp offset] / sp "@@" sp point]
 //!
 //! port = connector sp [integer]
+//!      / ? names in `[aliases]` of the config file ?
 //! connector = "edp" / "hdmi" / "dp"
 //!           / ? all other Connector variants in src/info.rs ?
 //!
-//! resolution = "720p" / "1080p" / "1200p" / "4k"
+//! mode = resolution [refresh]
+//! resolution = ? names in `[resolutions]` of the config file ?
+//!            / "720p" / "1080p" / "1200p" / "4k"
 //!            / ? all other Resolution variants in src/info.rs ?
+//!            ; only if exactly one of the above has that height
+//!            / integer "p"
 //!            ; custom resolution for more niche cases
 //!            / size
 //! size = integer sp "x" sp integer
 //!
+//! refresh = float
+//! refresh-range = refresh sp "-" sp refresh
+//!
 //! scale = float
 //!
 //! transform = ["flip"  sp] quarter-deg
 //!           /  "flip" [sp  quarter-deg]
 //! quarter-deg = "0" / "90" / "180" / "270"
 //!
-//! pos = hori [sp "," sp vert-spec]
-//!     / vert [sp "," sp hori-spec]
+//! option = "max_render_time" sp integer
+//!        / "subpixel" sp subpixel
+//!        / "render_bit_depth" sp ("8" / "10")
+//!        / "power" sp ("on" / "off")
+//!        / "bg" sp path sp bg-mode
+//!        / "order" sp coord
+//! subpixel = "rgb" / "bgr" / "vrgb" / "vbgr" / "none"
+//! path = DQUOTE *(%x20-21 / %x23-7E) DQUOTE
+//!      / 1*VCHAR
+//!      ; not starting with DQUOTE
+//! bg-mode = "fill" / "fit" / "stretch" / "center" / "tile"
+//!
+//! pos = hori [sp "," sp vert-spec [sp "," sp corner]]
+//!     / vert [sp "," sp hori-spec [sp "," sp corner]]
+//! anchored-pos = ("left-of" / "right-of") sp port [sp "," sp vert-spec [sp "," sp corner]]
+//!              / ("above" / "below") sp port [sp "," sp hori-spec [sp "," sp corner]]
+//! corner = ("upper" / "lower") "-" hori
+//! offset = "+" sp length sp "," sp length
+//! length = ["-"] float ("mm" / "cm" / "in")
+//!        / coord
+//! point = coord sp "," sp coord
+//! coord = ["-"] integer
 //! hori = "left" / "right"
 //! vert = "top" / "bottom"
-//! hori-spec = hori / "center"
-//! vert-spec = vert / "center"
+//! hori-spec = hori / "center" / fraction
+//! vert-spec = vert / "center" / fraction
+//! fraction = float ; from 0 to 1
 //!
 //! sp = *(WSP / CR / LF)
 //! integer / "0"
@@ -128,13 +365,67 @@
 //! # Notes
 //!
 //! - `port` number defaults to `1`
+//! - `?` marks a screen as optional
+//!     - If it isn't connected, it's skipped without a warning
+//!     - `--strict` refuses to apply the layout
+//!       if any screen without `?` isn't connected
+//!     - Profiles in the config file are chosen
+//!       even if their optional screens aren't connected
+//! - Screens on `headless` ports are created by the WM if they don't exist yet,
+//!   given that their resolution is specified
+//!     - The WM chooses the port number of created screens itself,
+//!       so it might be different from the one specified
 //! - `resolution` fetches the screen resolution from the WM
 //!   if left unspecified
-//! - `scale` always defaults to `1` if unspecified
-//!   and the screen isn't connected yet either
+//! - `refresh` is in Hz
+//!     - It can also directly follow a named resolution, e.g. `1080p60`
+//!       is the same as `1080p * 60`
+//!     - If a resolution is given, but no `refresh`, the WM chooses one
+//!     - If neither is given, the WM's current refresh rate is kept
+//!     - A range like `* 48-144` allows a variable refresh rate in between,
+//!       the screen is then driven at the maximum
+//!         - Sway can't be told the range itself,
+//!           so it only turns on adaptive sync there
+//!         - The minimum can't be above the maximum
+//! - If the WM knows which modes the screen supports,
+//!   `resolution` and `refresh` have to match one of them
+//!   (`refresh` may be off by up to half a Hz)
+//! - `scale` has to be greater than `0`
+//!     - `like` uses the scale of the given screen,
+//!       either as placed before in the same group or as currently set in the WM
+//!     - It always defaults to `1` if unspecified
+//!       and the screen isn't connected yet either
 //! - `transform`'s rotation is clockwise
+//!     - If unspecified, the current one is kept,
+//!       or no transform is assumed if the screen isn't connected yet
+//! - Only what's specified is sent to the WM,
+//!   except for the position which is always sent
+//!     - `--emit-all` sends everything,
+//!       including what's been kept from the current state of the WM
+//! - `order` changes in which order screens are placed,
+//!   without having to reorder the layout description
+//!     - Screens are placed by ascending `order`,
+//!       the ones with the same `order` in the order they're listed
+//!     - Defaults to `0`, so `-1` places a screen before all others
+//!     - Screens with an exact position are still placed first
+//! - All other `option`s are WM-specific and ignored by WMs which don't know them
+//!     - `max_render_time` is in milliseconds and only understood by Sway 1.4 onwards
+//!     - `subpixel` is the subpixel hinting, only understood by Sway 1.4 onwards
+//!     - `render_bit_depth` is the number of bits per color channel,
+//!       only understood by Sway 1.7 onwards
+//!     - `power off` turns the screen dark, only understood by Sway 1.7 onwards
+//!         - Unlike a disabled screen, it's still part of the layout
+//!           and keeps its workspaces
+//!     - `bg` is the background image, only included in exported Sway configuration
+//!       (`--no-apply`), but never applied directly
+//!         - `path` needs to be quoted if it contains whitespace
+//!     - Older Sway versions get them left out with a warning
+//!     - If the same `option` is given multiple times, the last one wins
 //! - `pos`
-//!     - Defaults to `right,top`
+//!     - Defaults to `right,top`, or `bottom,center` if the `flow` is `vertical`
+//!         - The `flow` of the layout takes precedence over the config file
+//!         - In the `[defaults]` table of the config file,
+//!           `position` takes precedence over `flow`
 //!         - If the `hori` version of pos is chosen, but no spec, `top` is assumed
 //!         - If the `vert` version of pos is chosen, but no spec, `center` is assumed
 //!     - Specifies on where to place the current screen
@@ -142,23 +433,189 @@
 //!       of all layout until now
 //!       so that the maximum edge is shared
 //!       while the position is still fulfilled
+//! - `anchored-pos`
+//!     - The anchor has to be in the same group before the screen,
+//!       either listed or placed (see `order`)
+//!     - If the anchor isn't connected,
+//!       the screen is placed as if it had the equivalent `pos` instead
 //!
 //! [ABNF]: https://datatracker.ietf.org/doc/html/rfc5234
-use std::{error::Error, fmt, str::FromStr};
+use std::{error::Error, fmt, ops::Range, str::FromStr};
 
-use chumsky::{error::Simple, prelude::*, text::whitespace, Parser};
+use chumsky::{
+    error::{Simple, SimpleReason},
+    prelude::*,
+    text::whitespace,
+    Parser,
+};
 
 use crate::{
+    absolute::{Background, BackgroundMode, RefreshRange, Subpixel},
     comms::Port,
-    geometry::{Hori, HoriSpec, Pixel, Rotation, Size, Transform, Vert, VertSpec},
+    geometry::{
+        Corner, Hori, HoriSpec, Length, MaybeCenter, Offset, Pixel, Point, Rotation, Size,
+        Transform, Vert, VertSpec,
+    },
     info::{Connector, Resolution},
-    relative::{Layout, Position, Screen},
+    relative::{Flow, Layout, Position, Screen, Target},
+    Map,
 };
 
+/// Names which can be used in layout descriptions
+/// in addition to the built-in ones,
+/// usually from the config file, see [`Config::names`](crate::config::Config::names).
+///
+/// They're matched before the built-in ones,
+/// so they should be checked with [`Resolution::shadowed_by`]
+/// and [`Connector::shadowed_by`] beforehand.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{comms::Port, geometry::Size, info::{Connector, Resolution}, parse::dsl::Names, relative::{Layout, Target}};
+/// let watch = Size { width: 396, height: 484 };
+/// let dp2 = Port { kind: Connector::DisplayPort, idx: 2 };
+/// let names = Names {
+///     resolutions: [("watch".to_string(), watch)].into(),
+///     aliases: [("main".to_string(), dp2)].into(),
+/// };
+///
+/// let relative = Layout::parse_with("dsi@watch60", &names)?;
+/// assert_eq!(relative.groups[0][0].resolution, Some(Resolution::Custom(watch)));
+/// assert!("dsi@watch60".parse::<Layout>().is_err());
+///
+/// let relative = Layout::parse_with("main + edp/below main", &names)?;
+/// assert_eq!(relative.groups[0][0].target, Target::Port(dp2));
+/// assert_eq!(relative.groups[0][1].anchor, Some(dp2));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Names {
+    /// Standing for the size they map to wherever a resolution is expected.
+    pub resolutions: Map<String, Size>,
+    /// Standing for the port they map to wherever a port is expected.
+    pub aliases: Map<String, Port>,
+}
+
 impl FromStr for Layout {
     type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        layout().parse(s).map_err(ParseError)
+        Self::parse_with(s, &Names::default())
+    }
+}
+
+impl Layout {
+    /// Like [`FromStr`], but also knows about `names`.
+    pub fn parse_with(s: &str, names: &Names) -> Result<Self, ParseError> {
+        layout(names).parse(s).map_err(ParseError)
+    }
+
+    /// Everything wrong with the layout description `s`
+    /// as structured data, e.g. for highlighting in an editor.
+    /// Empty if it parses fine.
+    ///
+    /// For messages meant for humans, use [`FromStr`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{parse::dsl::{Names, Severity}, relative::Layout};
+    /// let names = Names::default();
+    /// assert!(Layout::parse_diagnostics("dp + edp/bottom", &names).is_empty());
+    ///
+    /// let diagnostics = Layout::parse_diagnostics("dp + edp:0", &names);
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(diagnostics[0].span, 9..10);
+    /// assert_eq!(diagnostics[0].severity, Severity::Error);
+    /// ```
+    #[must_use]
+    pub fn parse_diagnostics(s: &str, names: &Names) -> Vec<Diagnostic> {
+        match layout(names).parse(s) {
+            Ok(_) => Vec::new(),
+            Err(errs) => errs.iter().map(|err| Diagnostic::new(err, s)).collect(),
+        }
+    }
+}
+
+/// One problem found while parsing, located in the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Byte offsets into the parsed string.
+    pub span: Range<usize>,
+    pub message: String,
+    pub severity: Severity,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Severity {
+    /// The layout description can't be used at all.
+    Error,
+}
+
+impl Diagnostic {
+    /// [`chumsky`] counts in [`char`]s, which is not what editors want.
+    fn new(err: &Simple<char>, source: &str) -> Self {
+        let byte_offset = |idx: usize| {
+            source
+                .char_indices()
+                .nth(idx)
+                .map_or(source.len(), |(offset, _)| offset)
+        };
+        let span = err.span();
+
+        Self {
+            span: byte_offset(span.start)..byte_offset(span.end),
+            message: describe(err),
+            severity: Severity::Error,
+        }
+    }
+}
+
+impl FromStr for Position {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        pos().padded().then_ignore(end()).parse(s).map_err(ParseError)
+    }
+}
+
+impl FromStr for Size {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        size()
+            .padded()
+            .then_ignore(end())
+            .parse(s)
+            .map_err(ParseError)
+    }
+}
+
+impl FromStr for VertSpec {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        vert_spec()
+            .padded()
+            .then_ignore(end())
+            .parse(s)
+            .map_err(ParseError)
+    }
+}
+
+impl FromStr for Port {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, &Names::default())
+    }
+}
+
+impl Port {
+    /// Like [`FromStr`], but also knows about `names`.
+    pub fn parse_with(s: &str, names: &Names) -> Result<Self, ParseError> {
+        port(names)
+            .padded()
+            .then_ignore(end())
+            .parse(s)
+            .map_err(ParseError)
     }
 }
 
@@ -168,12 +625,12 @@ pub struct ParseError(Vec<Simple<char>>);
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let [err] = self.0.as_slice() {
-            writeln!(f, "{err}")?;
+            writeln!(f, "{}", describe(err))?;
         } else {
             writeln!(f, "{} errors encountered:", self.0.len())?;
 
             for (i, err) in self.0.iter().enumerate() {
-                writeln!(f, "{}: {}", i + 1, err)?;
+                writeln!(f, "{}: {}", i + 1, describe(err))?;
             }
         }
 
@@ -186,59 +643,808 @@ impl fmt::Display for ParseError {
 
 impl Error for ParseError {}
 
+/// [`Simple`]'s own [`fmt::Display`] impl ignores custom messages,
+/// so they'd be lost otherwise.
+fn describe(err: &Simple<char>) -> String {
+    match err.reason() {
+        SimpleReason::Custom(msg) => msg.clone(),
+        _ => err.to_string(),
+    }
+}
+
 #[must_use]
-pub fn layout() -> impl Parser<char, Layout, Error = Simple<char>> {
-    screen()
-        .separated_by(just('+').padded())
+pub fn layout(names: &Names) -> impl Parser<char, Layout, Error = Simple<char>> {
+    flow()
+        .then_ignore(just(':').padded())
+        .or_not()
+        .then(group(names).separated_by(just(';').padded()))
         .then_ignore(end())
-        .map(|screens| Layout { screens })
+        .try_map(|(flow, groups), span| {
+            let layout = Layout { groups, flow };
+            let others = layout
+                .screens()
+                .filter(|screen| screen.target == Target::Others);
+            if others.count() > 1 {
+                return Err(Simple::custom(
+                    span,
+                    "`*` already stands for all other screens, so it can only appear once",
+                ));
+            }
+            Ok(layout)
+        })
+}
+
+#[must_use]
+pub fn flow() -> impl Parser<char, Flow, Error = Simple<char>> {
+    choice((
+        just("horizontal").to(Flow::Horizontal),
+        just("vertical").to(Flow::Vertical),
+        just("stack")
+            .ignore_then(whitespace().at_least(1).ignore_then(hori_spec()).or_not())
+            .map(|spec| Flow::Stack(spec.unwrap_or_default())),
+    ))
 }
 
 #[must_use]
-pub fn screen() -> impl Parser<char, Screen, Error = Simple<char>> {
-    let scale = float;
-    port()
-        .then(just('@').padded().ignore_then(resolution()).or_not())
-        .then(just(':').padded().ignore_then(scale()).or_not())
+pub fn group(names: &Names) -> impl Parser<char, Vec<Screen>, Error = Simple<char>> {
+    members(names)
+        .separated_by(separator())
+        .at_least(1)
+        .flatten()
+}
+
+/// Between screens. `+` is the canonical one,
+/// `,` and `&` are accepted too since other tools use them.
+///
+/// `,` also appears inside positions like `bottom,center`,
+/// but no port is called like a position, so that's unambiguous.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::relative::Layout;
+/// let same = |a: &str, b: &str| {
+///     let (a, b): (Layout, Layout) = (a.parse().unwrap(), b.parse().unwrap());
+///     format!("{a:?}") == format!("{b:?}")
+/// };
+/// assert!(same("dp + edp/bottom,center + hdmi", "dp, edp/bottom,center & hdmi"));
+/// assert!(same("dp + edp/bottom + hdmi", "dp&edp/bottom , hdmi"));
+/// assert!(same("{dp + hdmi}:2 + edp/bottom", "{dp & hdmi}:2, edp/bottom"));
+/// ```
+#[must_use]
+pub fn separator() -> impl Parser<char, (), Error = Simple<char>> {
+    one_of("+,&").padded().ignored()
+}
+
+/// Either a single screen,
+/// or multiple ones in braces which share the attributes after the closing brace.
+#[must_use]
+pub fn members(names: &Names) -> impl Parser<char, Vec<Screen>, Error = Simple<char>> {
+    recursive(|members| {
+        let shared = members
+            .separated_by(separator())
+            .at_least(1)
+            .flatten()
+            .delimited_by(just('{').padded(), just('}').padded())
+            .then(attrs(names))
+            .map(|(mut screens, shared): (Vec<Screen>, Attrs)| {
+                for (i, screen) in screens.iter_mut().enumerate() {
+                    shared.inherit_to(screen, i == 0);
+                }
+                screens
+            });
+
+        choice((
+            shorthand(names),
+            shared,
+            others(names).map(|screen| vec![screen]),
+            screen(names).map(|screen| vec![screen]),
+        ))
+    })
+}
+
+/// `*` followed by attributes, standing for all connected outputs
+/// not mentioned anywhere else, see [`Target::Others`].
+#[must_use]
+pub fn others(names: &Names) -> impl Parser<char, Screen, Error = Simple<char>> {
+    just('*')
+        .ignore_then(attrs(names))
+        .map(|attrs| attrs.into_screen(Target::Others))
+}
+
+/// Common setups which would be tedious to spell out each time,
+/// expanded into the screens they stand for.
+#[must_use]
+pub fn shorthand(names: &Names) -> impl Parser<char, Vec<Screen>, Error = Simple<char>> {
+    choice((laptop_below(names), grid(names)))
+}
+
+/// `laptop-below ext` is `ext + edp/bottom,center`.
+#[must_use]
+pub fn laptop_below(names: &Names) -> impl Parser<char, Vec<Screen>, Error = Simple<char>> {
+    just("laptop-below")
+        .ignore_then(whitespace().at_least(1))
+        .ignore_then(screen(names))
+        .map(|external| {
+            let laptop = Attrs {
+                pos: Some(Position::Vert {
+                    edge: Vert::Bottom,
+                    spec: HoriSpec::Center,
+                    corner: None,
+                }),
+                ..Attrs::default()
+            }
+            .into_screen(Target::Port(Port {
+                kind: Connector::Edp,
+                idx: 1,
+            }));
+
+            vec![external, laptop]
+        })
+}
+
+/// `grid 2x2 [dp1 dp2 dp3 dp4]` places the screens in 2 columns and 2 rows,
+/// filling up each row before the next one.
+/// Each screen is right of the one before in its row, tops aligned,
+/// and each row is below the first screen of the row before, lefts aligned.
+/// So screens of the same size form an exact grid.
+///
+/// Like braces, attributes after the closing bracket apply to all screens.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::relative::Layout;
+/// let same = |a: &str, b: &str| {
+///     let (a, b): (Layout, Layout) = (a.parse().unwrap(), b.parse().unwrap());
+///     format!("{a:?}") == format!("{b:?}")
+/// };
+/// assert!(same(
+///     "grid 2x2 [dp1 dp2 dp3 dp4@1080p]:1.5",
+///     "dp1:1.5 + dp2:1.5/right-of dp1,top + dp3:1.5/below dp1,left + dp4@1080p:1.5/right-of dp3,top",
+/// ));
+/// assert!(same("grid 3x1 [dp1 + dp2 + dp3]", "dp1 + dp2/right-of dp1,top + dp3/right-of dp2,top"));
+///
+/// assert!("grid 2x2 [dp1 dp2 dp3]".parse::<Layout>().is_err());
+/// ```
+#[must_use]
+pub fn grid(names: &Names) -> impl Parser<char, Vec<Screen>, Error = Simple<char>> {
+    let cell_sep = separator().or(whitespace().at_least(1).ignored());
+    let cells = screen(names)
+        .separated_by(cell_sep)
+        .at_least(1)
+        .delimited_by(just('[').padded(), just(']').padded());
+
+    just("grid")
+        .ignore_then(whitespace().at_least(1))
+        .ignore_then(integer().then_ignore(just('x')).then(integer()))
+        .then(cells)
+        .try_map(|((columns, rows), mut screens), span| {
+            let expected = columns as usize * rows as usize;
+            if screens.len() != expected {
+                return Err(Simple::custom(
+                    span,
+                    format!(
+                        "grid {columns}x{rows} needs exactly {expected} screens, but has {}",
+                        screens.len(),
+                    ),
+                ));
+            }
+
+            let columns = columns as usize;
+            for i in 1..screens.len() {
+                let (anchor, pos) = if i % columns == 0 {
+                    let pos = Position::Vert {
+                        edge: Vert::Bottom,
+                        spec: HoriSpec::Extreme(Hori::Left),
+                        corner: None,
+                    };
+                    (screens[i - columns].port(), pos)
+                } else {
+                    let pos = Position::Hori {
+                        edge: Hori::Right,
+                        spec: VertSpec::Extreme(Vert::Top),
+                        corner: None,
+                    };
+                    (screens[i - 1].port(), pos)
+                };
+                screens[i].pos = Some(pos);
+                screens[i].anchor = Some(anchor);
+            }
+            Ok(screens)
+        })
+        .then(attrs(names))
+        .map(|(mut screens, shared)| {
+            for (i, screen) in screens.iter_mut().enumerate() {
+                shared.inherit_to(screen, i == 0);
+            }
+            screens
+        })
+}
+
+#[must_use]
+pub fn screen(names: &Names) -> impl Parser<char, Screen, Error = Simple<char>> {
+    port(names)
+        .then(just('?').or_not())
+        .then(attrs(names))
+        .map(|((port, optional), attrs)| Screen {
+            optional: optional.is_some(),
+            ..attrs.into_screen(Target::Port(port))
+        })
+}
+
+/// Everything about a screen except for its port.
+#[derive(Clone, Debug, Default)]
+struct Attrs {
+    resolution: Option<Resolution>,
+    refresh: Option<f64>,
+    refresh_range: Option<RefreshRange>,
+    scale: Option<f64>,
+    scale_like: Option<Port>,
+    transform: Option<Transform>,
+    max_render_time: Option<u32>,
+    subpixel: Option<Subpixel>,
+    render_bit_depth: Option<u8>,
+    power: Option<bool>,
+    background: Option<Background>,
+    order: Option<i32>,
+    pos: Option<Position>,
+    anchor: Option<Port>,
+    offset: Option<Offset>,
+    at: Option<Point>,
+}
+
+impl Attrs {
+    fn into_screen(self, target: Target) -> Screen {
+        Screen {
+            target,
+            optional: false,
+            resolution: self.resolution,
+            refresh: self.refresh,
+            refresh_range: self.refresh_range,
+            scale: self.scale,
+            scale_like: self.scale_like,
+            transform: self.transform,
+            max_render_time: self.max_render_time,
+            subpixel: self.subpixel,
+            render_bit_depth: self.render_bit_depth,
+            power: self.power,
+            background: self.background,
+            order: self.order,
+            pos: self.pos,
+            anchor: self.anchor,
+            offset: self.offset,
+            at: self.at,
+        }
+    }
+
+    /// Fills in all attributes `screen` doesn't specify itself.
+    /// The position is only filled in if the screen is the `first` one,
+    /// since it's where all others continue from.
+    fn inherit_to(&self, screen: &mut Screen, first: bool) {
+        screen.resolution = screen.resolution.or(self.resolution);
+        if screen.refresh.is_none() {
+            screen.refresh = self.refresh;
+            screen.refresh_range = self.refresh_range;
+        }
+        if screen.scale.is_none() && screen.scale_like.is_none() {
+            screen.scale = self.scale;
+            screen.scale_like = self.scale_like;
+        }
+        screen.transform = screen.transform.or(self.transform);
+        screen.max_render_time = screen.max_render_time.or(self.max_render_time);
+        screen.subpixel = screen.subpixel.or(self.subpixel);
+        screen.render_bit_depth = screen.render_bit_depth.or(self.render_bit_depth);
+        screen.power = screen.power.or(self.power);
+        screen.background = screen.background.take().or_else(|| self.background.clone());
+        screen.order = screen.order.or(self.order);
+        if first && screen.pos.is_none() && screen.at.is_none() {
+            screen.pos = self.pos;
+            screen.anchor = self.anchor;
+            screen.offset = self.offset;
+            screen.at = self.at;
+        }
+    }
+}
+
+fn attrs(names: &Names) -> impl Parser<char, Attrs, Error = Simple<char>> {
+    just('@')
+        .padded()
+        .ignore_then(mode(names))
+        .or_not()
+        .then(just('*').padded().ignore_then(refresh_or_range()).or_not())
+        .then(
+            just(':')
+                .padded()
+                .ignore_then(scale_or_like(names))
+                .or_not(),
+        )
         .then(just('#').padded().ignore_then(transform()).or_not())
-        .then(just('/').padded().ignore_then(pos()).or_not())
-        .map(|((((port, resolution), scale), transform), pos)| Screen {
-            port,
-            resolution,
-            scale,
-            transform: transform.unwrap_or_default(),
-            pos: pos.unwrap_or_default(),
+        .then(just('%').padded().ignore_then(option()).repeated())
+        .then(
+            just('/')
+                .padded()
+                .ignore_then(
+                    anchored_pos(names)
+                        .map(|(pos, anchor)| (pos, Some(anchor)))
+                        .or(pos().map(|pos| (pos, None)))
+                        .then(offset().or_not())
+                        .map(|((pos, anchor), offset)| Placement::Relative {
+                            pos,
+                            anchor,
+                            offset,
+                        }),
+                )
+                .or(just("@@").padded().ignore_then(point()).map(Placement::At))
+                .or_not(),
+        )
+        .map(
+            |(((((mode, refresh), scale), transform), options), placement)| {
+                let (refresh, refresh_range) = refresh.unzip();
+                let refresh_range = refresh_range.flatten();
+                let (resolution, refresh) = match mode {
+                    Some((resolution, refresh_in_mode)) => {
+                        (Some(resolution), refresh.or(refresh_in_mode))
+                    }
+                    None => (None, refresh),
+                };
+                let (scale, scale_like) = scale.unwrap_or_default();
+
+                let mut attrs = Attrs {
+                    resolution,
+                    refresh,
+                    refresh_range,
+                    scale,
+                    scale_like,
+                    transform,
+                    ..Attrs::default()
+                };
+                match placement {
+                    Some(Placement::Relative {
+                        pos,
+                        anchor,
+                        offset,
+                    }) => {
+                        attrs.pos = Some(pos);
+                        attrs.anchor = anchor;
+                        attrs.offset = offset;
+                    }
+                    Some(Placement::At(at)) => attrs.at = Some(at),
+                    None => (),
+                }
+
+                for option in options {
+                    match option {
+                        Opt::MaxRenderTime(ms) => attrs.max_render_time = Some(ms),
+                        Opt::Subpixel(subpixel) => attrs.subpixel = Some(subpixel),
+                        Opt::RenderBitDepth(depth) => attrs.render_bit_depth = Some(depth),
+                        Opt::Power(on) => attrs.power = Some(on),
+                        Opt::Background(bg) => attrs.background = Some(bg),
+                        Opt::Order(order) => attrs.order = Some(order),
+                    }
+                }
+
+                attrs
+            },
+        )
+}
+
+/// Where a screen is placed, if it says so at all.
+#[derive(Clone, Copy, Debug)]
+enum Placement {
+    Relative {
+        pos: Position,
+        anchor: Option<Port>,
+        offset: Option<Offset>,
+    },
+    At(Point),
+}
+
+/// WM-specific setting of a screen
+/// which is too niche to warrant its own sigil.
+#[derive(Clone, Debug)]
+pub enum Opt {
+    MaxRenderTime(u32),
+    Subpixel(Subpixel),
+    RenderBitDepth(u8),
+    Power(bool),
+    Background(Background),
+    /// Not WM-specific, but still too niche.
+    Order(i32),
+}
+
+#[must_use]
+pub fn option() -> impl Parser<char, Opt, Error = Simple<char>> {
+    let max_render_time = just("max_render_time")
+        .ignore_then(whitespace())
+        .ignore_then(integer())
+        .map(Opt::MaxRenderTime);
+    let subpixel = just("subpixel")
+        .ignore_then(whitespace())
+        .ignore_then(subpixel())
+        .map(Opt::Subpixel);
+    let render_bit_depth = just("render_bit_depth")
+        .ignore_then(whitespace())
+        .ignore_then(integer())
+        .try_map(|depth, span| match depth {
+            8 => Ok(8),
+            10 => Ok(10),
+            _ => Err(Simple::custom(
+                span,
+                format!("render bit depth can only be 8 or 10, not {depth}"),
+            )),
+        })
+        .map(Opt::RenderBitDepth);
+
+    let power = just("power")
+        .ignore_then(whitespace())
+        .ignore_then(choice((just("on").to(true), just("off").to(false))))
+        .map(Opt::Power);
+
+    let background = just("bg")
+        .ignore_then(whitespace())
+        .ignore_then(path())
+        .then_ignore(whitespace())
+        .then(background_mode())
+        .map(|(path, mode)| {
+            Opt::Background(Background {
+                path: path.into(),
+                mode,
+            })
+        });
+
+    let order = just("order")
+        .ignore_then(whitespace())
+        .ignore_then(just('-').or_not())
+        .then(integer())
+        .try_map(|(minus, abs), span| {
+            let abs = i32::try_from(abs)
+                .map_err(|_| Simple::custom(span, format!("order {abs} is too large")))?;
+            Ok(if minus.is_some() { -abs } else { abs })
         })
+        .map(Opt::Order);
+
+    choice((
+        max_render_time,
+        subpixel,
+        render_bit_depth,
+        power,
+        background,
+        order,
+    ))
+}
+
+/// A file path, in double quotes if it contains whitespace.
+///
+/// # Examples
+///
+/// ```
+/// # use chumsky::Parser;
+/// # use layaway::parse::dsl::path;
+/// assert_eq!(path().parse("~/wall.png").unwrap(), "~/wall.png");
+/// assert_eq!(path().parse("\"~/my wall.png\"").unwrap(), "~/my wall.png");
+/// ```
+#[must_use]
+pub fn path() -> impl Parser<char, String, Error = Simple<char>> {
+    let quoted = none_of('"')
+        .repeated()
+        .at_least(1)
+        .delimited_by(just('"'), just('"'));
+    let bare = filter(|c: &char| !c.is_whitespace() && *c != '"')
+        .chain(filter(|c: &char| !c.is_whitespace()).repeated());
+
+    quoted.or(bare).collect()
+}
+
+#[must_use]
+pub fn background_mode() -> impl Parser<char, BackgroundMode, Error = Simple<char>> {
+    choice((
+        just("fill").to(BackgroundMode::Fill),
+        just("fit").to(BackgroundMode::Fit),
+        just("stretch").to(BackgroundMode::Stretch),
+        just("center").to(BackgroundMode::Center),
+        just("tile").to(BackgroundMode::Tile),
+    ))
+}
+
+#[must_use]
+pub fn subpixel() -> impl Parser<char, Subpixel, Error = Simple<char>> {
+    choice((
+        just("rgb").to(Subpixel::Rgb),
+        just("bgr").to(Subpixel::Bgr),
+        just("vrgb").to(Subpixel::Vrgb),
+        just("vbgr").to(Subpixel::Vbgr),
+        just("none").to(Subpixel::None),
+    ))
 }
 
 #[allow(clippy::missing_panics_doc)] // cannot panic since that'd mean parsing failed already
 #[must_use]
-pub fn port() -> impl Parser<char, Port, Error = Simple<char>> {
-    Connector::parse_from_name()
+pub fn port(names: &Names) -> impl Parser<char, Port, Error = Simple<char>> {
+    let by_connector = connector()
         .then(integer().or_not())
         .map(|(kind, idx)| Port {
             kind,
             idx: idx.unwrap_or(1),
+        });
+
+    alias(names).or(by_connector)
+}
+
+/// One of [`Names::aliases`].
+#[must_use]
+pub fn alias(names: &Names) -> impl Parser<char, Port, Error = Simple<char>> {
+    let mut aliases: Vec<_> = names.aliases.clone().into_iter().collect();
+    // see the note on prefixes in `info`
+    aliases.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+
+    let aliases: Vec<_> = aliases
+        .into_iter()
+        .map(|(name, port)| just(name).to(port))
+        .collect();
+    if aliases.is_empty() {
+        // choice would panic on no alternatives at all
+        return filter_map(|span, found| Err(Simple::expected_input_found(span, [], Some(found))))
+            .boxed();
+    }
+    choice(aliases).boxed()
+}
+
+/// Like [`Connector::parse_from_name`],
+/// but suggests similarly named connectors on a typo.
+#[must_use]
+pub fn connector() -> impl Parser<char, Connector, Error = Simple<char>> {
+    filter(char::is_ascii_alphabetic)
+        .repeated()
+        .at_least(1)
+        .collect::<String>()
+        .try_map(|name, span| {
+            Connector::from_dsl_name(&name).ok_or_else(|| {
+                let hint = match similar(&name, Connector::dsl_names()).as_slice() {
+                    [] => String::new(),
+                    [single] => format!(", did you mean {single}?"),
+                    multiple => format!(", did you mean one of {}?", multiple.join(", ")),
+                };
+                let msg = format!("unknown connector `{name}`{hint}");
+                Simple::custom(span, msg)
+            })
         })
 }
 
+/// Up to three of the `candidates` which are only a few typos away from `typo`,
+/// closest first, formatted as code.
+fn similar(typo: &str, candidates: &[&str]) -> Vec<String> {
+    const MAX_DISTANCE: usize = 2;
+    const MAX_SUGGESTIONS: usize = 3;
+
+    let mut close: Vec<_> = candidates
+        .iter()
+        .map(|candidate| (edit_distance(typo, candidate), candidate))
+        .filter(|(distance, _)| *distance <= MAX_DISTANCE)
+        .collect();
+    // stable, so equally close ones stay in their original order
+    close.sort_by_key(|(distance, _)| *distance);
+
+    close
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| format!("`{candidate}`"))
+        .collect()
+}
+
+/// How many characters have to be inserted, deleted, substituted
+/// or swapped with their neighbor to turn `a` into `b`,
+/// also known as the optimal string alignment distance.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // dist[i][j] is the distance between the first i chars of a and the first j chars of b
+    let mut dist = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in dist.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in dist[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitute = dist[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let delete = dist[i - 1][j] + 1;
+            let insert = dist[i][j - 1] + 1;
+            dist[i][j] = substitute.min(delete).min(insert);
+
+            let swapped = i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1];
+            if swapped {
+                dist[i][j] = dist[i][j].min(dist[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    dist[a.len()][b.len()]
+}
+
+/// A resolution, optionally directly followed by a refresh rate, like `1080p60`.
+#[must_use]
+pub fn mode(names: &Names) -> impl Parser<char, (Resolution, Option<f64>), Error = Simple<char>> {
+    // custom sizes eat all digits anyway, so this is unambiguous
+    resolution(names).then(refresh().or_not())
+}
+
 #[must_use]
-pub fn resolution() -> impl Parser<char, Resolution, Error = Simple<char>> {
+pub fn resolution(names: &Names) -> impl Parser<char, Resolution, Error = Simple<char>> {
     choice((
+        custom_resolution(names),
         Resolution::parse_from_name(),
+        resolution_by_height(),
         size().map(Resolution::Custom),
     ))
 }
 
+/// One of [`Names::resolutions`].
+#[must_use]
+pub fn custom_resolution(names: &Names) -> impl Parser<char, Resolution, Error = Simple<char>> {
+    let mut names: Vec<_> = names.resolutions.clone().into_iter().collect();
+    // see the note on prefixes in `info`
+    names.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+
+    let names: Vec<_> = names
+        .into_iter()
+        .map(|(name, size)| just(name).to(Resolution::Custom(size)))
+        .collect();
+    if names.is_empty() {
+        // choice would panic on no alternatives at all
+        return filter_map(|span, found| Err(Simple::expected_input_found(span, [], Some(found))))
+            .boxed();
+    }
+    choice(names).boxed()
+}
+
+/// Resolution specified only by its height followed by `p`, like `2160p`.
+/// Only matches if there is exactly one named resolution with that height.
+#[allow(clippy::cast_possible_wrap)] // see size
+#[must_use]
+pub fn resolution_by_height() -> impl Parser<char, Resolution, Error = Simple<char>> {
+    integer()
+        .then_ignore(just('p'))
+        .try_map(|height, span| {
+            match Resolution::with_height(height as Pixel).as_slice() {
+                [res] => Ok(*res),
+                [] => Err(Simple::custom(
+                    span,
+                    format!(
+                        "no known resolution is {height} pixels high, \
+                        consider specifying the whole size like `1920x{height}`"
+                    ),
+                )),
+                candidates => Err(Simple::custom(
+                    span,
+                    format!(
+                        "multiple resolutions are {height} pixels high, \
+                        use one of their names instead: {}",
+                        candidates
+                            .iter()
+                            .filter_map(Resolution::dsl_name)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ),
+                )),
+            }
+        })
+}
+
+/// Has to be greater than zero,
+/// anything else would make the screen infinitely large or flip it.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::relative::Layout;
+/// assert!("edp:1.5".parse::<Layout>().is_ok());
+/// assert!("edp:0".parse::<Layout>().is_err());
+/// // negative numbers aren't even part of the grammar
+/// assert!("edp:-1".parse::<Layout>().is_err());
+/// ```
+#[must_use]
+pub fn scale() -> impl Parser<char, f64, Error = Simple<char>> {
+    float().try_map(|scale, span| {
+        if scale > 0.0 {
+            Ok(scale)
+        } else {
+            Err(Simple::custom(span, "scale has to be greater than 0"))
+        }
+    })
+}
+
+/// Either a [`scale`] or `like` followed by the port whose scale to use.
+#[must_use]
+pub fn scale_or_like(
+    names: &Names,
+) -> impl Parser<char, (Option<f64>, Option<Port>), Error = Simple<char>> {
+    just("like")
+        .ignore_then(whitespace())
+        .ignore_then(port(names))
+        .map(|port| (None, Some(port)))
+        .or(scale().map(|scale| (Some(scale), None)))
+}
+
+/// In Hz.
+#[must_use]
+pub fn refresh() -> impl Parser<char, f64, Error = Simple<char>> {
+    float()
+}
+
+/// A refresh rate, or a range of them for variable refresh rate like `48-144`.
+/// For a range, the refresh rate is its maximum.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{absolute::RefreshRange, relative::Layout};
+/// let layout: Layout = "dp@1080p*48-144".parse()?;
+/// let dp = &layout.groups[0][0];
+/// assert_eq!(dp.refresh, Some(144.0));
+/// assert_eq!(dp.refresh_range, Some(RefreshRange { min: 48.0, max: 144.0 }));
+///
+/// assert!("dp@1080p*144-48".parse::<Layout>().is_err());
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[must_use]
+pub fn refresh_or_range() -> impl Parser<char, (f64, Option<RefreshRange>), Error = Simple<char>> {
+    refresh()
+        .then(just('-').padded().ignore_then(refresh()).or_not())
+        .try_map(|(min, max), span| match max {
+            None => Ok((min, None)),
+            Some(max) if min <= max => Ok((max, Some(RefreshRange { min, max }))),
+            Some(max) => Err(Simple::custom(
+                span,
+                format!("refresh range has to go from low to high, but {min} is above {max}"),
+            )),
+        })
+}
+
+/// Width and height separated by `x`, like `2560x1440`.
+/// Neither may be zero, since the screen would have no area then.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::relative::Layout;
+/// assert!("edp@2560x1440".parse::<Layout>().is_ok());
+/// assert!("edp@0x1080".parse::<Layout>().is_err());
+/// assert!("edp@1920x0".parse::<Layout>().is_err());
+/// ```
 #[allow(clippy::cast_possible_wrap)] // the edge case of a screen's resolution being this high is bearably unlikely
 #[must_use]
 pub fn size() -> impl Parser<char, Size, Error = Simple<char>> {
     integer()
         .then_ignore(just('x').padded())
         .then(integer())
-        .map(|(width, height)| Size {
-            width: width as Pixel,
-            height: height as Pixel,
+        .try_map(|(width, height), span| {
+            let zero = match (width, height) {
+                (0, 0) => "width and height",
+                (0, _) => "width",
+                (_, 0) => "height",
+                _ => {
+                    // would wrap around otherwise
+                    let (Ok(width), Ok(height)) = (Pixel::try_from(width), Pixel::try_from(height))
+                    else {
+                        return Err(Simple::custom(
+                            span,
+                            format!(
+                                "{width}x{height} is too large, neither side can exceed {}",
+                                Pixel::MAX
+                            ),
+                        ));
+                    };
+                    return Ok(Size { width, height });
+                }
+            };
+            Err(Simple::custom(
+                span,
+                format!("{zero} of {width}x{height} has to be greater than 0"),
+            ))
         })
 }
 
@@ -258,31 +1464,172 @@ pub fn transform() -> impl Parser<char, Transform, Error = Simple<char>> {
 
 #[must_use]
 pub fn rotation() -> impl Parser<char, Rotation, Error = Simple<char>> {
-    let none = just('0').to(Rotation::None);
-    let quarter = just("90").to(Rotation::Quarter);
-    let half = just("180").to(Rotation::Half);
-    let three_quarter = just("270").to(Rotation::ThreeQuarter);
-
-    choice((none, quarter, half, three_quarter))
+    integer().try_map(|degrees, span| {
+        u16::try_from(degrees)
+            .ok()
+            .and_then(Rotation::from_degrees)
+            .ok_or_else(|| {
+                Simple::custom(
+                    span,
+                    format!("rotation can only be 0, 90, 180 or 270, not {degrees}"),
+                )
+            })
+    })
 }
 
 #[must_use]
 pub fn pos() -> impl Parser<char, Position, Error = Simple<char>> {
-    let hori_then_vert = hori().then(just(',').padded().ignore_then(vert_spec()).or_not());
-    let vert_then_hori = vert().then(just(',').padded().ignore_then(hori_spec()).or_not());
+    let hori_then_vert = hori().then(spec_then_corner(vert_spec()));
+    let vert_then_hori = vert().then(spec_then_corner(hori_spec()));
+
+    choice((
+        hori_then_vert.map(|(edge, (spec, corner))| Position::Hori { edge, spec, corner }),
+        vert_then_hori.map(|(edge, (spec, corner))| Position::Vert { edge, spec, corner }),
+    ))
+}
+
+/// The optional parts of a position after its edge:
+/// where exactly on the edge, then which corner touches there.
+/// The corner can only be given after the spec.
+fn spec_then_corner<T: Clone + Copy + fmt::Debug>(
+    spec: impl Parser<char, MaybeCenter<T>, Error = Simple<char>>,
+) -> impl Parser<char, (MaybeCenter<T>, Option<Corner>), Error = Simple<char>>
+where
+    MaybeCenter<T>: Default,
+{
+    just(',')
+        .padded()
+        .ignore_then(spec.then(just(',').padded().ignore_then(corner()).or_not()))
+        .or_not()
+        .map(Option::unwrap_or_default)
+}
+
+/// A corner of a screen, like `lower-left`.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{
+/// #     comms::{offline::Offline, Port},
+/// #     geometry::{Interval, Rect},
+/// #     info::Connector,
+/// #     relative,
+/// # };
+/// // the lower left corner of edp touches the upper right one of dp
+/// let relative: relative::Layout = "dp@1080p + edp@1080p/right,top,lower-left".parse()?;
+/// let layout = relative.to_absolute(&mut Offline::default())?.layout;
+/// let edp = layout.outputs[&Port { kind: Connector::Edp, idx: 1 }].bounds;
+/// let dp = layout.outputs[&Port { kind: Connector::DisplayPort, idx: 1 }].bounds;
+/// assert_eq!(edp.x.start(), dp.x.end());
+/// assert_eq!(edp.y.end(), dp.y.start());
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[must_use]
+pub fn corner() -> impl Parser<char, Corner, Error = Simple<char>> {
+    let vert = choice((just("upper").to(Vert::Top), just("lower").to(Vert::Bottom)));
+
+    vert.then_ignore(just('-'))
+        .then(hori())
+        .map(|(vert, hori)| Corner { hori, vert })
+}
+
+/// Position next to a specific other screen, the anchor,
+/// rather than next to the bounding box of all screens until now.
+#[must_use]
+pub fn anchored_pos(names: &Names) -> impl Parser<char, (Position, Port), Error = Simple<char>> {
+    let hori = choice((
+        just("left-of").to(Hori::Left),
+        just("right-of").to(Hori::Right),
+    ));
+    let vert = choice((just("above").to(Vert::Top), just("below").to(Vert::Bottom)));
+
+    let hori_then_vert = hori
+        .then_ignore(whitespace())
+        .then(port(names))
+        .then(spec_then_corner(vert_spec()));
+    let vert_then_hori = vert
+        .then_ignore(whitespace())
+        .then(port(names))
+        .then(spec_then_corner(hori_spec()));
 
     choice((
-        hori_then_vert.map(|(hori, vert)| Position::Hori {
-            edge: hori,
-            spec: vert.unwrap_or_default(),
+        hori_then_vert.map(|((edge, anchor), (spec, corner))| {
+            (Position::Hori { edge, spec, corner }, anchor)
         }),
-        vert_then_hori.map(|(vert, hori)| Position::Vert {
-            edge: vert,
-            spec: hori.unwrap_or_default(),
+        vert_then_hori.map(|((edge, anchor), (spec, corner))| {
+            (Position::Vert { edge, spec, corner }, anchor)
         }),
     ))
 }
 
+/// Nudge after placing a screen, as `+x,y`.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{geometry::Length, relative::Layout};
+/// let layout: Layout = "dp + edp/bottom,right +10,-5 + hdmi".parse()?;
+/// let edp = &layout.groups[0][1];
+/// assert_eq!(
+///     edp.offset.map(|offset| (offset.x, offset.y)),
+///     Some((Length::Pixels(10), Length::Pixels(-5))),
+/// );
+/// assert_eq!(layout.groups[0].len(), 3);
+///
+/// let layout: Layout = "dp + hdmi/right +-1.5cm,2in".parse()?;
+/// let hdmi = &layout.groups[0][1];
+/// assert_eq!(
+///     hdmi.offset.map(|offset| (offset.x, offset.y)),
+///     Some((Length::Millimeters(-15.0), Length::Millimeters(50.8))),
+/// );
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[must_use]
+pub fn offset() -> impl Parser<char, Offset, Error = Simple<char>> {
+    just('+')
+        .padded()
+        .ignore_then(separated(length(), length()))
+        .map(|(x, y)| Offset { x, y })
+}
+
+/// Either a [`coord`] in pixels,
+/// or a physical length with a unit, like `-1.5cm`.
+#[must_use]
+pub fn length() -> impl Parser<char, Length, Error = Simple<char>> {
+    let mm_per_unit = choice((just("mm").to(1.0), just("cm").to(10.0), just("in").to(25.4)));
+    let physical =
+        just('-')
+            .or_not()
+            .then(float())
+            .then(mm_per_unit)
+            .map(|((minus, len), mm_per_unit)| {
+                let mm = len * mm_per_unit;
+                Length::Millimeters(if minus.is_some() { -mm } else { mm })
+            });
+
+    choice((physical, coord().map(Length::Pixels)))
+}
+
+/// Exact position as `x,y`.
+#[must_use]
+pub fn point() -> impl Parser<char, Point, Error = Simple<char>> {
+    separated(coord(), coord()).map(|(x, y)| Point { x, y })
+}
+
+/// Like [`integer`], but may be negative.
+#[must_use]
+pub fn coord() -> impl Parser<char, Pixel, Error = Simple<char>> {
+    just('-')
+        .or_not()
+        .then(integer())
+        .try_map(|(minus, abs), span| {
+            let abs = Pixel::try_from(abs).map_err(|_| {
+                Simple::custom(span, format!("{abs} is too large for a coordinate"))
+            })?;
+            Ok(if minus.is_some() { -abs } else { abs })
+        })
+}
+
 pub fn separated<T, U>(
     a: impl Parser<char, T, Error = Simple<char>>,
     b: impl Parser<char, U, Error = Simple<char>>,
@@ -300,7 +1647,11 @@ pub fn hori() -> impl Parser<char, Hori, Error = Simple<char>> {
 
 #[must_use]
 pub fn hori_spec() -> impl Parser<char, HoriSpec, Error = Simple<char>> {
-    choice((hori().map(Into::into), just("center").to(HoriSpec::Center)))
+    choice((
+        hori().map(Into::into),
+        just("center").to(HoriSpec::Center),
+        fraction().map(HoriSpec::Along),
+    ))
 }
 
 #[must_use]
@@ -313,7 +1664,41 @@ pub fn vert() -> impl Parser<char, Vert, Error = Simple<char>> {
 
 #[must_use]
 pub fn vert_spec() -> impl Parser<char, VertSpec, Error = Simple<char>> {
-    choice((vert().map(Into::into), just("center").to(VertSpec::Center)))
+    choice((
+        vert().map(Into::into),
+        just("center").to(VertSpec::Center),
+        fraction().map(VertSpec::Along),
+    ))
+}
+
+/// How far along an edge something is, from 0 to 1.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{geometry::MaybeCenter, relative::{Layout, Position}};
+/// let layout: Layout = "dp + edp/right,0.3 +0,-20".parse()?;
+/// let edp = &layout.groups[0][1];
+/// let Some(Position::Hori { spec: MaybeCenter::Along(fraction), .. }) = edp.pos else {
+///     panic!("edp should be placed along the edge");
+/// };
+/// assert!((fraction - 0.3).abs() < f64::EPSILON);
+///
+/// assert!("dp + edp/right,1.5".parse::<Layout>().is_err());
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[must_use]
+pub fn fraction() -> impl Parser<char, f64, Error = Simple<char>> {
+    float().try_map(|fraction, span| {
+        if (0.0..=1.0).contains(&fraction) {
+            Ok(fraction)
+        } else {
+            Err(Simple::custom(
+                span,
+                format!("position along an edge has to be from 0 to 1, not {fraction}"),
+            ))
+        }
+    })
 }
 
 // the ones below cannot panic, otherwise parsing would've failed already
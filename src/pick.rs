@@ -0,0 +1,263 @@
+//! Arranging outputs interactively in the terminal,
+//! for when one doesn't know the connector names (yet).
+//!
+//! Each output is a box on a grid,
+//! which can be moved around with the arrow keys.
+//! Once done, the arrangement is turned into a layout description.
+
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use thiserror::Error;
+
+use crate::{
+    absolute::{self, OutputRef},
+    comms::Port,
+    Map,
+};
+
+/// Width of a box in terminal cells, including its border.
+const BOX_WIDTH: u16 = 18;
+/// Height of a box in terminal cells, including its border.
+const BOX_HEIGHT: u16 = 5;
+/// Lines above the grid, explaining the keys.
+const HELP: &[&str] = &[
+    "arrows: move output    tab: select next    enter: done    esc: abort",
+    "",
+];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("On the terminal: {0}")]
+    Terminal(#[from] io::Error),
+    #[error("There are no outputs to arrange")]
+    NoOutputs,
+}
+
+/// Lets the user arrange all outputs of `current`,
+/// starting out from left to right as they're currently placed.
+///
+/// Returns the layout description of the arrangement,
+/// or [`None`] if the user aborted.
+pub fn pick(current: &absolute::Layout) -> Result<Option<String>, Error> {
+    let outputs = current.outputs_sorted_by_position();
+    if outputs.is_empty() {
+        return Err(Error::NoOutputs);
+    }
+
+    let mut arrangement = Arrangement::in_a_row(outputs.iter().map(|output| *output.port));
+    let mut selected = 0;
+
+    let _terminal = Terminal::enter()?;
+    let mut out = io::stderr();
+
+    loop {
+        draw(&mut out, &arrangement, &outputs, selected)?;
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+
+        let port = *outputs[selected].port;
+        match code {
+            KeyCode::Left => arrangement.shift(port, -1, 0),
+            KeyCode::Right => arrangement.shift(port, 1, 0),
+            KeyCode::Up => arrangement.shift(port, 0, -1),
+            KeyCode::Down => arrangement.shift(port, 0, 1),
+            KeyCode::Tab => selected = (selected + 1) % outputs.len(),
+            KeyCode::BackTab => selected = (selected + outputs.len() - 1) % outputs.len(),
+            KeyCode::Enter => return Ok(Some(arrangement.to_desc())),
+            KeyCode::Esc | KeyCode::Char('q') => return Ok(None),
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            _ => (),
+        }
+    }
+}
+
+/// Where each output is on a grid,
+/// as column and row.
+///
+/// Always starts at column and row 0,
+/// but may have gaps.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Arrangement {
+    pub cells: Map<Port, (i32, i32)>,
+}
+
+impl Arrangement {
+    /// All `ports` next to each other, in the given order.
+    pub fn in_a_row(ports: impl IntoIterator<Item = Port>) -> Self {
+        let cells = ports
+            .into_iter()
+            .zip(0..)
+            .map(|(port, col)| (port, (col, 0)));
+        Self {
+            cells: cells.collect(),
+        }
+    }
+
+    /// Moves `port` by `cols` and `rows`,
+    /// swapping places with whichever output is already there.
+    pub fn shift(&mut self, port: Port, cols: i32, rows: i32) {
+        let Some(&(col, row)) = self.cells.get(&port) else {
+            return;
+        };
+        let target = (col + cols, row + rows);
+
+        if let Some(occupant) = self.at(target) {
+            self.cells.insert(occupant, (col, row));
+        }
+        self.cells.insert(port, target);
+        self.normalize();
+    }
+
+    /// Turns the arrangement into a layout description,
+    /// placing each output next to the one on its left or above it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{comms::Port, info::Connector, pick::Arrangement};
+    /// let dp1 = Port { kind: Connector::DisplayPort, idx: 1 };
+    /// let dp2 = Port { kind: Connector::DisplayPort, idx: 2 };
+    /// let edp = Port { kind: Connector::Edp, idx: 1 };
+    ///
+    /// let mut arrangement = Arrangement::in_a_row([dp1, dp2, edp]);
+    /// assert_eq!(arrangement.to_desc(), "dp1 + dp2/right-of dp1 + edp1/right-of dp2");
+    ///
+    /// arrangement.shift(edp, 0, 1);
+    /// arrangement.shift(edp, -2, 0);
+    /// assert_eq!(arrangement.to_desc(), "dp1 + dp2/right-of dp1 + edp1/below dp1");
+    /// ```
+    #[must_use]
+    pub fn to_desc(&self) -> String {
+        let mut order: Vec<_> = self
+            .cells
+            .iter()
+            .map(|(&port, &cell)| (cell, port))
+            .collect();
+        order.sort_by_key(|&((col, row), port)| (row, col, port));
+
+        let screens = order.iter().enumerate().map(|(i, &((col, row), port))| {
+            let placed = &order[..i];
+            let name = port.dsl_name();
+            if placed.is_empty() {
+                return name;
+            }
+
+            // the nearest one in the same row or column, if any
+            let left = placed
+                .iter()
+                .filter(|((other_col, other_row), _)| *other_row == row && *other_col < col)
+                .max_by_key(|((other_col, _), _)| *other_col);
+            let above = placed
+                .iter()
+                .filter(|((other_col, other_row), _)| *other_col == col && *other_row < row)
+                .max_by_key(|((_, other_row), _)| *other_row);
+
+            match (left, above) {
+                (Some((_, anchor)), _) => format!("{name}/right-of {}", anchor.dsl_name()),
+                (None, Some((_, anchor))) => format!("{name}/below {}", anchor.dsl_name()),
+                // starts a new row without anything above it
+                (None, None) => format!("{name}/bottom,left"),
+            }
+        });
+
+        screens.collect::<Vec<_>>().join(" + ")
+    }
+
+    /// The output at `cell`, if any.
+    fn at(&self, cell: (i32, i32)) -> Option<Port> {
+        self.cells
+            .iter()
+            .find(|(_, &other)| other == cell)
+            .map(|(&port, _)| port)
+    }
+
+    /// Moves everything so the least column and row are 0 again.
+    fn normalize(&mut self) {
+        let min_col = self.cells.values().map(|&(col, _)| col).min().unwrap_or(0);
+        let min_row = self.cells.values().map(|&(_, row)| row).min().unwrap_or(0);
+        for (col, row) in self.cells.values_mut() {
+            *col -= min_col;
+            *row -= min_row;
+        }
+    }
+}
+
+/// Puts the terminal into raw mode on a separate screen
+/// while it lives, and back into its normal state on drop.
+struct Terminal;
+
+impl Terminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stderr(), EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        // nothing left to do about it if it fails anyway
+        let _ = execute!(io::stderr(), cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Draws each output as box at its cell, with the `selected` one highlighted.
+fn draw(
+    out: &mut impl Write,
+    arrangement: &Arrangement,
+    outputs: &[OutputRef<'_>],
+    selected: usize,
+) -> io::Result<()> {
+    queue!(out, terminal::Clear(ClearType::All))?;
+    for (line, text) in (0..).zip(HELP) {
+        queue!(out, cursor::MoveTo(0, line), Print(text))?;
+    }
+
+    for (i, output) in outputs.iter().enumerate() {
+        let Some(&(col, row)) = arrangement.cells.get(output.port) else {
+            continue;
+        };
+        let x = u16::try_from(col).unwrap_or(u16::MAX / BOX_WIDTH) * BOX_WIDTH;
+        let y = u16::try_from(row).unwrap_or(u16::MAX / BOX_HEIGHT) * BOX_HEIGHT
+            + u16::try_from(HELP.len()).unwrap_or_default();
+
+        let inner = usize::from(BOX_WIDTH - 2);
+        let resolution = output
+            .cfg
+            .resolution
+            .map_or_else(|| "off".to_string(), |res| res.to_string());
+        let lines = [
+            format!("┌{}┐", "─".repeat(inner)),
+            format!("│{:^inner$}│", output.port.dsl_name()),
+            format!("│{:^inner$}│", output.port.to_string()),
+            format!("│{resolution:^inner$}│"),
+            format!("└{}┘", "─".repeat(inner)),
+        ];
+
+        if i == selected {
+            queue!(out, SetAttribute(Attribute::Reverse))?;
+        }
+        for (offset, line) in (0..).zip(lines) {
+            queue!(out, cursor::MoveTo(x, y + offset), Print(line))?;
+        }
+        queue!(out, SetAttribute(Attribute::Reset))?;
+    }
+
+    out.flush()
+}
@@ -0,0 +1,189 @@
+//! Makes layouts readable for humans.
+
+use std::{fmt::Write, iter};
+
+use crate::{
+    absolute::{self, Mode, OutputConfig, OutputRef},
+    relative::{self, Position},
+};
+
+/// Formats all outputs of the layout as a table
+/// with aligned columns, one row per output,
+/// ordered by their position.
+#[must_use]
+pub fn table(layout: &absolute::Layout) -> String {
+    let header = [
+        "PORT",
+        "STATUS",
+        "RESOLUTION",
+        "SCALE",
+        "TRANSFORM",
+        "POSITION",
+        "MONITOR",
+    ]
+    .map(String::from);
+
+    let rows = layout
+        .outputs_sorted_by_position()
+        .into_iter()
+        .map(|output| {
+            let cfg = output.cfg;
+            [
+                output.port.to_string(),
+                status(cfg).to_string(),
+                cfg.resolution
+                    .map_or_else(|| "-".to_string(), |res| res.to_string()),
+                cfg.scale
+                    .map_or_else(|| "-".to_string(), |scale| scale.to_string()),
+                cfg.transform.to_sway(),
+                format!("{},{}", cfg.bounds.x.start(), cfg.bounds.y.start()),
+                // last since it contains spaces itself
+                cfg.identity
+                    .as_ref()
+                    .map_or_else(|| "-".to_string(), ToString::to_string),
+            ]
+        });
+
+    align(&[header].into_iter().chain(rows).collect::<Vec<_>>())
+}
+
+/// Formats everything known about a single output,
+/// one attribute per line, followed by all `modes` it supports.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{
+/// #     absolute::{Mode, OutputConfig, OutputRef},
+/// #     comms::Port,
+/// #     geometry::Size,
+/// #     info::Connector,
+/// #     present,
+/// # };
+/// let size = Size { width: 1920, height: 1080 };
+/// let cfg = OutputConfig {
+///     resolution: Some(size),
+///     refresh: Some(60.0),
+///     active: true,
+///     ..OutputConfig::default()
+/// };
+/// let port = Port { kind: Connector::DisplayPort, idx: 2 };
+/// let modes = [Mode { size, refresh: 60.0 }, Mode { size, refresh: 50.0 }];
+///
+/// let info = present::info(OutputRef { port: &port, cfg: &cfg }, &modes);
+/// assert!(info.starts_with("PORT        DP-2\n"));
+/// assert!(info.ends_with("MODES       1920x1080@60Hz\n            1920x1080@50Hz\n"));
+/// ```
+#[must_use]
+pub fn info(output: OutputRef<'_>, modes: &[Mode]) -> String {
+    let cfg = output.cfg;
+    let unknown = || "-".to_string();
+
+    let mut rows = vec![
+        ("PORT", output.port.to_string()),
+        (
+            "MONITOR",
+            cfg.identity
+                .as_ref()
+                .map_or_else(unknown, ToString::to_string),
+        ),
+        ("STATUS", status(cfg).to_string()),
+        (
+            "RESOLUTION",
+            cfg.resolution.map_or_else(unknown, |res| res.to_string()),
+        ),
+        (
+            "REFRESH",
+            cfg.refresh.map_or_else(unknown, |hz| format!("{hz}Hz")),
+        ),
+        (
+            "SCALE",
+            cfg.scale.map_or_else(unknown, |scale| scale.to_string()),
+        ),
+        ("TRANSFORM", cfg.transform.to_sway()),
+        (
+            "POSITION",
+            format!("{},{}", cfg.bounds.x.start(), cfg.bounds.y.start()),
+        ),
+    ];
+    // only label the first one, the others are aligned below it
+    let labels = iter::once("MODES").chain(iter::repeat(""));
+    rows.extend(
+        labels
+            .zip(modes)
+            .map(|(label, mode)| (label, mode.to_string())),
+    );
+    if modes.is_empty() {
+        rows.push(("MODES", unknown()));
+    }
+
+    let rows: Vec<_> = rows
+        .into_iter()
+        .map(|(label, value)| [label.to_string(), value])
+        .collect();
+    align(&rows)
+}
+
+/// Explains for each screen of `relative`
+/// where and why it ended up in `placed`, one line per screen.
+/// `default_pos` is the position of screens which don't specify one,
+/// see [`relative::Screen::describe`].
+#[must_use]
+pub fn explain(
+    relative: &relative::Layout,
+    placed: &absolute::Layout,
+    default_pos: Position,
+) -> String {
+    // the others are placed by now, so they're exactly the ones not mentioned
+    let relative = relative.with_others_expanded(placed);
+    let screens = relative
+        .groups
+        .iter()
+        .flat_map(|group| group.iter().enumerate());
+    screens
+        .map(|(i, screen)| {
+            let desc = screen.describe(default_pos, i == 0);
+            match placed.outputs.get(&screen.port()) {
+                Some(cfg) => format!(
+                    "{desc}, so it's at {},{} with a size of {}\n",
+                    cfg.bounds.x.start(),
+                    cfg.bounds.y.start(),
+                    cfg.bounds.size(),
+                ),
+                None => format!("{desc}, but it's not connected, so it's skipped\n"),
+            }
+        })
+        .collect()
+}
+
+/// Whether the output is on at all.
+fn status(cfg: &OutputConfig) -> &'static str {
+    // powered off ones are still part of the layout, just dark
+    match (cfg.active, cfg.power) {
+        (false, _) => "disabled",
+        (true, Some(false)) => "powered off",
+        (true, _) => "active",
+    }
+}
+
+/// Pads each cell so that all columns start at the same position,
+/// separating columns by two spaces.
+fn align<const N: usize>(rows: &[[String; N]]) -> String {
+    let mut widths = [0; N];
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    for row in rows {
+        let mut line = String::new();
+        for (cell, width) in row.iter().zip(widths) {
+            write!(line, "{cell:<width$}  ").unwrap();
+        }
+        writeln!(out, "{}", line.trim_end()).unwrap();
+    }
+
+    out
+}
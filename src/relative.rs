@@ -1,36 +1,612 @@
+use std::fmt;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
 use crate::{
+    absolute::{Background, RefreshRange, Subpixel},
     comms::Port,
-    geometry::{Hori, MaybeCenter, Transform, Vert},
+    geometry::{Corner, Hori, HoriSpec, MaybeCenter, Offset, Point, Transform, Vert, VertSpec},
     info::Resolution,
 };
 
 /// Description of a screen layout,
 /// based on relative positioning.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Layout {
-    pub screens: Vec<Screen>,
+    /// Independent groups of screens.
+    /// Each group is laid out on its own,
+    /// then the groups are placed next to each other.
+    pub groups: Vec<Vec<Screen>>,
+    /// In which direction screens without explicit position are added.
+    /// [`None`] if not explicitly specified,
+    /// in which case the default position of the conversion is used.
+    pub flow: Option<Flow>,
+}
+
+impl Layout {
+    /// All screens of all groups.
+    pub fn screens(&self) -> impl Iterator<Item = &Screen> {
+        self.groups.iter().flatten()
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Screen {
-    pub port: Port,
+    /// Which outputs this screen configures.
+    pub target: Target,
+    /// If the layout is fine without this screen,
+    /// so it's skipped silently if it isn't connected,
+    /// even with [`Options::strict`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     comms::{offline::Offline, Port},
+    /// #     convert::{Error, Options},
+    /// #     info::Connector,
+    /// #     relative,
+    /// # };
+    /// let strict = Options { strict: true, ..Options::default() };
+    ///
+    /// let relative: relative::Layout = "headless@1080p + dp2?@1080p".parse()?;
+    /// let layout = relative.to_absolute_with(&mut Offline::default(), &strict)?.layout;
+    /// let dp2 = Port { kind: Connector::DisplayPort, idx: 2 };
+    /// assert!(!layout.outputs.contains_key(&dp2));
+    ///
+    /// let relative: relative::Layout = "headless@1080p + dp2@1080p".parse()?;
+    /// assert!(matches!(
+    ///     relative.to_absolute_with(&mut Offline::default(), &strict),
+    ///     Err(Error::Missing { .. }),
+    /// ));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [`Options::strict`]: crate::convert::Options::strict
+    pub optional: bool,
+    /// Always in the native orientation of the screen,
+    /// i.e. before [`Self::transform`] is applied,
+    /// just like the modes the screen reports.
+    /// A panel which is natively portrait stays portrait here,
+    /// even if it's mounted and rotated as landscape.
+    ///
+    /// The only exception is a screen turned sideways
+    /// whose modes only have the resolution with width and height swapped.
+    /// Then it was most likely given as it looks after rotating,
+    /// so the swapped one is used instead with a
+    /// [`Warning::SidewaysMode`](crate::convert::Warning::SidewaysMode).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute,
+    /// #     comms::{self, offline::Offline, Comms, Port},
+    /// #     geometry::{Interval, Rect, Rotation, Size, Transform},
+    /// #     info::Connector,
+    /// #     relative,
+    /// # };
+    /// let dsi = Port { kind: Connector::Dsi, idx: 1 };
+    /// let landscape = Size { width: 1920, height: 1080 };
+    ///
+    /// let relative: relative::Layout = "dsi@1080x1920 #90".parse()?;
+    /// let layout = relative.to_absolute(&mut Offline::default())?.layout;
+    /// let cfg = &layout.outputs[&dsi];
+    /// assert_eq!(cfg.resolution, Some(Size { width: 1080, height: 1920 }));
+    /// assert_eq!(cfg.bounds.size(), landscape);
+    ///
+    /// // also if the resolution is the one the panel currently has,
+    /// // which is unaffected by how it's currently rotated
+    /// struct Rotated;
+    /// impl Comms for Rotated {
+    ///     fn layout(&mut self) -> comms::Result<absolute::Layout> {
+    ///         let cfg = absolute::OutputConfig {
+    ///             bounds: Rect { x: Interval::new(0, 1920), y: Interval::new(0, 1080) },
+    ///             resolution: Some(Size { width: 1080, height: 1920 }),
+    ///             transform: Transform { flipped: false, rotation: Rotation::Quarter },
+    ///             active: true,
+    ///             ..absolute::OutputConfig::default()
+    ///         };
+    ///         Ok([absolute::Output { port: Port { kind: Connector::Dsi, idx: 1 }, cfg }]
+    ///             .into_iter()
+    ///             .collect())
+    ///     }
+    ///     # fn set_layout(&mut self, _: &absolute::Layout) -> comms::Result<()> {
+    ///     #     Ok(())
+    ///     # }
+    /// }
+    /// let relative: relative::Layout = "dsi #90".parse()?;
+    /// let layout = relative.to_absolute(&mut Rotated)?.layout;
+    /// assert_eq!(layout.outputs[&dsi].bounds.size(), landscape);
+    ///
+    /// let relative: relative::Layout = "dsi #0".parse()?;
+    /// let layout = relative.to_absolute(&mut Rotated)?.layout;
+    /// assert_eq!(layout.outputs[&dsi].bounds.size(), Size { width: 1080, height: 1920 });
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
     pub resolution: Option<Resolution>,
+    /// In Hz.
+    /// If [`Self::refresh_range`] is set, its maximum.
+    pub refresh: Option<f64>,
+    /// See [`absolute::OutputConfig::refresh_range`].
+    ///
+    /// [`absolute::OutputConfig::refresh_range`]: crate::absolute::OutputConfig::refresh_range
+    pub refresh_range: Option<RefreshRange>,
     pub scale: Option<f64>,
-    pub transform: Transform,
-    pub pos: Position,
+    /// Which other screen to take the scale from,
+    /// if [`Self::scale`] isn't specified.
+    /// Either the one placed before in the same group,
+    /// or the one currently set in the WM.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{comms::{offline::Offline, Port}, info::Connector, relative};
+    /// let relative: relative::Layout = "dp1@4k:1.5 + dp2@4k:like dp1".parse()?;
+    /// let layout = relative.to_absolute(&mut Offline::default())?.layout;
+    /// let dp2 = &layout.outputs[&Port { kind: Connector::DisplayPort, idx: 2 }];
+    /// assert_eq!(dp2.scale, Some(1.5));
+    ///
+    /// // nothing to take it from
+    /// let relative: relative::Layout = "dp2@4k:like dp1".parse()?;
+    /// assert!(relative.to_absolute(&mut Offline::default()).is_err());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub scale_like: Option<Port>,
+    /// [`None`] if not explicitly specified,
+    /// in which case the current one is kept.
+    pub transform: Option<Transform>,
+    /// Sway-specific, see [`absolute::OutputConfig::max_render_time`].
+    ///
+    /// [`absolute::OutputConfig::max_render_time`]: crate::absolute::OutputConfig::max_render_time
+    pub max_render_time: Option<u32>,
+    /// Sway-specific, see [`absolute::OutputConfig::subpixel`].
+    ///
+    /// [`absolute::OutputConfig::subpixel`]: crate::absolute::OutputConfig::subpixel
+    pub subpixel: Option<Subpixel>,
+    /// Sway-specific, see [`absolute::OutputConfig::render_bit_depth`].
+    ///
+    /// [`absolute::OutputConfig::render_bit_depth`]: crate::absolute::OutputConfig::render_bit_depth
+    pub render_bit_depth: Option<u8>,
+    /// Sway-specific, see [`absolute::OutputConfig::power`].
+    ///
+    /// [`absolute::OutputConfig::power`]: crate::absolute::OutputConfig::power
+    pub power: Option<bool>,
+    /// Only exported, see [`absolute::OutputConfig::background`].
+    ///
+    /// [`absolute::OutputConfig::background`]: crate::absolute::OutputConfig::background
+    pub background: Option<Background>,
+    /// Screens are placed by ascending order,
+    /// and in the order they're listed if it's the same.
+    /// [`None`] is the same as `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{comms::{offline::Offline, Port}, info::Connector, relative};
+    /// let x = |desc: &str, kind| -> Result<_, Box<dyn std::error::Error>> {
+    ///     let relative: relative::Layout = desc.parse()?;
+    ///     let layout = relative.to_absolute(&mut Offline::default())?.layout;
+    ///     Ok(layout.outputs[&Port { kind, idx: 1 }].bounds.x.start())
+    /// };
+    ///
+    /// // the laptop is listed last, but placed first, so the others can refer to it
+    /// let desc = "dp@1080p/right-of edp + hdmi@1080p/left-of edp + edp@1080p % order -1";
+    /// assert_eq!(x(desc, Connector::HdmiA)?, 0);
+    /// assert_eq!(x(desc, Connector::Edp)?, 1920);
+    /// assert_eq!(x(desc, Connector::DisplayPort)?, 3840);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub order: Option<i32>,
+    /// [`None`] if not explicitly specified,
+    /// in which case the default position of the conversion is used.
+    pub pos: Option<Position>,
+    /// Which earlier screen of the same group [`Self::pos`] refers to.
+    /// [`None`] if it refers to all screens placed before it.
+    pub anchor: Option<Port>,
+    /// Moves the screen by this much after placing it according to [`Self::pos`].
+    /// [`None`] if it stays where [`Self::pos`] places it.
+    /// Physical lengths are converted using how large the screen itself is physically.
+    pub offset: Option<Offset>,
+    /// Exact upper left corner, overriding [`Self::pos`].
+    /// Neither its group nor the whole layout is moved afterwards,
+    /// see [`convert::Options::reset_to_origin`](crate::convert::Options::reset_to_origin).
+    /// [`None`] if the screen is placed relative to the others.
+    ///
+    /// Such screens are placed before all others in their group,
+    /// so it doesn't matter where they're listed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{comms::{offline::Offline, Port}, geometry::Point, info::Connector, relative};
+    /// let corners = |desc: &str| -> Result<Vec<Point>, Box<dyn std::error::Error>> {
+    ///     let relative: relative::Layout = desc.parse()?;
+    ///     let layout = relative.to_absolute(&mut Offline::default())?.layout;
+    ///     let corner = |kind| {
+    ///         let bounds = layout.outputs[&Port { kind, idx: 1 }].bounds;
+    ///         Point { x: bounds.x.start(), y: bounds.y.start() }
+    ///     };
+    ///     Ok([Connector::Edp, Connector::DisplayPort, Connector::HdmiA].map(corner).into())
+    /// };
+    ///
+    /// let interleaved = corners("dp@1080p + edp@1080p@@0,1080 + hdmi@1080p/bottom,left")?;
+    /// assert_eq!(
+    ///     interleaved,
+    ///     [Point { x: 0, y: 1080 }, Point { x: 1920, y: 1080 }, Point { x: 0, y: 2160 }],
+    /// );
+    /// assert_eq!(
+    ///     interleaved,
+    ///     corners("edp@1080p@@0,1080 + dp@1080p + hdmi@1080p/bottom,left")?,
+    /// );
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub at: Option<Point>,
+}
+
+/// What a [`Screen`] configures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// The output at this port.
+    Port(Port),
+    /// All connected outputs not mentioned anywhere else in the layout, written as `*`.
+    /// They're placed where this screen is listed, ordered by port,
+    /// and each gets the attributes of the screen.
+    /// Only the first of them is placed at its position,
+    /// the others follow the flow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{
+    /// #     absolute::{self, Output, OutputConfig},
+    /// #     comms::{self, Comms, Port},
+    /// #     geometry::Size,
+    /// #     info::Connector,
+    /// #     relative,
+    /// # };
+    /// struct Docked;
+    /// impl Comms for Docked {
+    ///     fn layout(&mut self) -> comms::Result<absolute::Layout> {
+    ///         let output = |kind, idx| Output {
+    ///             port: Port { kind, idx },
+    ///             cfg: OutputConfig {
+    ///                 resolution: Some(Size { width: 3840, height: 2160 }),
+    ///                 active: true,
+    ///                 ..OutputConfig::default()
+    ///             },
+    ///         };
+    ///         let outputs = [
+    ///             output(Connector::Edp, 1),
+    ///             output(Connector::DisplayPort, 2),
+    ///             output(Connector::DisplayPort, 1),
+    ///         ];
+    ///         Ok(outputs.into_iter().collect())
+    ///     }
+    ///     # fn set_layout(&mut self, _: &absolute::Layout) -> comms::Result<()> {
+    ///     #     Ok(())
+    ///     # }
+    /// }
+    ///
+    /// let relative: relative::Layout = "*:2 + edp@1080p/bottom".parse()?;
+    /// let layout = relative.to_absolute(&mut Docked)?.layout;
+    /// let cfg = |kind, idx| &layout.outputs[&Port { kind, idx }];
+    ///
+    /// assert_eq!(cfg(Connector::DisplayPort, 1).bounds.x.start(), 0);
+    /// assert_eq!(cfg(Connector::DisplayPort, 2).bounds.x.start(), 1920);
+    /// assert_eq!(cfg(Connector::DisplayPort, 2).scale, Some(2.0));
+    /// // mentioned on its own, so not part of `*`
+    /// assert_eq!(cfg(Connector::Edp, 1).bounds.x.start(), 960);
+    /// assert_eq!(cfg(Connector::Edp, 1).scale, Some(1.0));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    Others,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub enum Position {
-    Hori { edge: Hori, spec: MaybeCenter<Vert> },
-    Vert { edge: Vert, spec: MaybeCenter<Hori> },
+    Hori {
+        edge: Hori,
+        spec: MaybeCenter<Vert>,
+        /// Which corner of the placed screen touches the point `spec` selects,
+        /// [`None`] to choose it from `edge` and `spec` like the DSL describes.
+        corner: Option<Corner>,
+    },
+    Vert {
+        edge: Vert,
+        spec: MaybeCenter<Hori>,
+        /// Like the one of [`Position::Hori`].
+        corner: Option<Corner>,
+    },
+}
+
+impl Screen {
+    /// The port of this screen.
+    ///
+    /// # Panics
+    ///
+    /// If this is the [`Target::Others`] screen,
+    /// which has to be expanded into the ones it stands for beforehand.
+    pub(crate) fn port(&self) -> Port {
+        match self.target {
+            Target::Port(port) => port,
+            Target::Others => unreachable!("`*` should have been expanded by now"),
+        }
+    }
+
+    /// How this screen is placed, in plain English.
+    /// `default_pos` is what the conversion uses
+    /// if the screen doesn't specify a position itself.
+    /// If `first`, there's nothing placed before it in its group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::relative::{Layout, Position};
+    /// let layout: Layout = "dp + edp/below dp,left +0,20".parse()?;
+    /// let [dp, edp] = &layout.groups[0][..] else { unreachable!() };
+    ///
+    /// assert_eq!(dp.describe(Position::default(), true), "DP-1 starts its group");
+    /// assert_eq!(
+    ///     edp.describe(Position::default(), false),
+    ///     "eDP-1 is below DP-1, left edges aligned, then moved by 0,20",
+    /// );
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn describe(&self, default_pos: Position, first: bool) -> String {
+        let target = self.target;
+        if let Some(at) = self.at {
+            return format!("{target} is at exactly {},{} in its group", at.x, at.y);
+        }
+        if first && self.anchor.is_none() {
+            return format!("{target} starts its group");
+        }
+
+        let reference = self.anchor.map_or_else(
+            || "the bounding box of the screens before it".to_string(),
+            |anchor| anchor.to_string(),
+        );
+        let offset = self
+            .offset
+            .map_or_else(String::new, |offset| format!(", then moved by {offset}"));
+        format!(
+            "{target} is {}{offset}",
+            self.pos.unwrap_or(default_pos).describe(&reference),
+        )
+    }
 }
 
 impl Default for Position {
     fn default() -> Self {
-        Self::Hori {
-            edge: Hori::default(),
-            spec: MaybeCenter::Extreme(Vert::Top),
+        Flow::default().default_pos()
+    }
+}
+
+/// Direction in which the bounding box grows
+/// when adding screens without explicit position.
+///
+/// # Examples
+///
+/// ```
+/// # use layaway::{comms::offline::Offline, geometry::Interval, relative};
+/// let horizontal: relative::Layout = "dp@1080p + hdmi@1080p + edp@1080p".parse()?;
+/// let layout = horizontal.to_absolute(&mut Offline::default())?.layout;
+/// let xs: Vec<_> = layout.outputs().map(|output| output.cfg.bounds.x).collect();
+/// assert_eq!(
+///     xs,
+///     [Interval::new(0, 1920), Interval::new(1920, 3840), Interval::new(3840, 5760)],
+/// );
+///
+/// let vertical: relative::Layout = "vertical: dp@1080p + hdmi@1080p + edp@1080p".parse()?;
+/// let layout = vertical.to_absolute(&mut Offline::default())?.layout;
+/// let ys: Vec<_> = layout.outputs().map(|output| output.cfg.bounds.y).collect();
+/// assert_eq!(
+///     ys,
+///     [Interval::new(0, 1080), Interval::new(1080, 2160), Interval::new(2160, 3240)],
+/// );
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+// the example above is of no use in the schema
+#[schemars(
+    description = "Direction in which the bounding box grows when adding screens without explicit position."
+)]
+pub enum Flow {
+    /// From left to right, aligning the upper edges.
+    #[default]
+    Horizontal,
+    /// From top to bottom, aligning the centers.
+    Vertical,
+    /// From top to bottom like [`Self::Vertical`],
+    /// but aligning all screens below each other at the given spec,
+    /// even the ones with a position of their own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::{comms::offline::Offline, geometry::{Interval, Point}, relative};
+    /// let corners = |desc: &str| -> Result<Vec<Point>, Box<dyn std::error::Error>> {
+    ///     let relative: relative::Layout = desc.parse()?;
+    ///     let layout = relative.to_absolute(&mut Offline::default())?.layout;
+    ///     let corners = layout
+    ///         .outputs()
+    ///         .map(|output| Point { x: output.cfg.bounds.x.start(), y: output.cfg.bounds.y.start() });
+    ///     Ok(corners.collect())
+    /// };
+    ///
+    /// let portrait = "stack left: dp1@1440x2560 + dp2@1440x2560 + dp3@1440x2560";
+    /// assert_eq!(
+    ///     corners(portrait)?,
+    ///     [Point { x: 0, y: 0 }, Point { x: 0, y: 2560 }, Point { x: 0, y: 5120 }],
+    /// );
+    ///
+    /// // dp2 would be centered on its own, but the stack aligns it anyway
+    /// let mixed = "stack right: dp1@1440x2560 + dp2@1080p/bottom + dp3@1440x2560";
+    /// assert_eq!(
+    ///     corners(mixed)?,
+    ///     [Point { x: 480, y: 0 }, Point { x: 0, y: 2560 }, Point { x: 480, y: 3640 }],
+    /// );
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    // the DSL spells out the spec, which serde can't
+    #[serde(skip)]
+    Stack(HoriSpec),
+}
+
+impl Flow {
+    /// Where screens without explicit position are placed in this flow.
+    #[must_use]
+    pub fn default_pos(self) -> Position {
+        match self {
+            Self::Horizontal => Position::Hori {
+                edge: Hori::Right,
+                spec: VertSpec::default(),
+                corner: None,
+            },
+            Self::Vertical => Position::Vert {
+                edge: Vert::Bottom,
+                spec: HoriSpec::default(),
+                corner: None,
+            },
+            Self::Stack(spec) => Position::Vert {
+                edge: Vert::Bottom,
+                spec,
+                corner: None,
+            },
         }
     }
 }
+
+impl Position {
+    /// Where this position places a screen next to `reference`, in plain English.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use layaway::relative::Position;
+    /// let pos: Position = "bottom,center".parse()?;
+    /// assert_eq!(pos.describe("DP-1"), "below DP-1, horizontally centered");
+    ///
+    /// let pos: Position = "left,bottom".parse()?;
+    /// assert_eq!(pos.describe("DP-1"), "left of DP-1, bottom edges aligned");
+    ///
+    /// let pos: Position = "right,0.3".parse()?;
+    /// assert_eq!(pos.describe("DP-1"), "right of DP-1, 30% of the way down");
+    ///
+    /// let pos: Position = "right,top,lower-left".parse()?;
+    /// assert_eq!(
+    ///     pos.describe("DP-1"),
+    ///     "right of DP-1, top edges aligned, touching with its lower left corner",
+    /// );
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn describe(&self, reference: &str) -> String {
+        let (side, alignment) = match *self {
+            Self::Hori { edge, spec, .. } => (
+                match edge {
+                    Hori::Left => "left of",
+                    Hori::Right => "right of",
+                },
+                match spec {
+                    MaybeCenter::Extreme(vert) => format!("{} edges aligned", vert_name(vert)),
+                    MaybeCenter::Center => "vertically centered".to_string(),
+                    MaybeCenter::Along(fraction) => {
+                        format!("{:.0}% of the way down", fraction * 100.0)
+                    }
+                },
+            ),
+            Self::Vert { edge, spec, .. } => (
+                match edge {
+                    Vert::Top => "above",
+                    Vert::Bottom => "below",
+                },
+                match spec {
+                    MaybeCenter::Extreme(hori) => format!("{} edges aligned", hori_name(hori)),
+                    MaybeCenter::Center => "horizontally centered".to_string(),
+                    MaybeCenter::Along(fraction) => {
+                        format!("{:.0}% of the way to the right", fraction * 100.0)
+                    }
+                },
+            ),
+        };
+
+        match self.corner() {
+            Some(corner) => format!(
+                "{side} {reference}, {alignment}, touching with its {} {} corner",
+                corner_vert_name(corner.vert),
+                hori_name(corner.hori),
+            ),
+            None => format!("{side} {reference}, {alignment}"),
+        }
+    }
+
+    /// Which corner of the placed screen is explicitly chosen to touch, if any.
+    #[must_use]
+    pub fn corner(&self) -> Option<Corner> {
+        match *self {
+            Self::Hori { corner, .. } | Self::Vert { corner, .. } => corner,
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Port(port) => port.fmt(f),
+            Self::Others => f.write_str("*"),
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    /// Formats the position in the DSL's syntax,
+    /// so that it could be parsed again.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (edge, spec) = match self {
+            Self::Hori { edge, spec, .. } => (hori_name(*edge), spec.map(vert_name)),
+            Self::Vert { edge, spec, .. } => (vert_name(*edge), spec.map(hori_name)),
+        };
+        let spec = match spec {
+            MaybeCenter::Extreme(spec) => spec.to_string(),
+            MaybeCenter::Center => "center".to_string(),
+            MaybeCenter::Along(fraction) => fraction.to_string(),
+        };
+
+        write!(f, "{edge},{spec}")?;
+        if let Some(corner) = self.corner() {
+            write!(
+                f,
+                ",{}-{}",
+                corner_vert_name(corner.vert),
+                hori_name(corner.hori)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn hori_name(hori: Hori) -> &'static str {
+    match hori {
+        Hori::Left => "left",
+        Hori::Right => "right",
+    }
+}
+
+fn vert_name(vert: Vert) -> &'static str {
+    match vert {
+        Vert::Top => "top",
+        Vert::Bottom => "bottom",
+    }
+}
+
+/// How a corner's vertical side is called in the DSL.
+fn corner_vert_name(vert: Vert) -> &'static str {
+    match vert {
+        Vert::Top => "upper",
+        Vert::Bottom => "lower",
+    }
+}